@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::hooks::HookChannel;
+use crate::notification::NotificationInfo;
+use crate::settings::Settings;
+use crate::webhook::WebhookChannel;
+
+/// A destination for quota alerts (desktop notification, log, file, ...)
+pub trait AlertChannel: Send + Sync {
+    /// Short name used in log messages
+    fn name(&self) -> &str;
+
+    /// Deliver a single alert
+    fn send(&self, info: &NotificationInfo) -> Result<()>;
+}
+
+/// Deliver an alert through every channel, logging failures instead of aborting
+pub fn dispatch(channels: &[Box<dyn AlertChannel>], info: &NotificationInfo) {
+    for channel in channels {
+        if let Err(e) = channel.send(info) {
+            eprintln!("[NotifAI] {} alert failed: {:#}", channel.name(), e);
+        }
+    }
+}
+
+/// Channels configured in settings, used in addition to the built-in ones
+/// (desktop notifications in the app, log output in headless mode)
+pub fn configured_channels(settings: &Settings) -> Vec<Box<dyn AlertChannel>> {
+    let mut channels: Vec<Box<dyn AlertChannel>> = settings
+        .webhook_urls
+        .iter()
+        .map(|url| Box::new(WebhookChannel::new(url)) as Box<dyn AlertChannel>)
+        .collect();
+    let hook = settings.hooks.on_notification.trim();
+    if !hook.is_empty() {
+        channels.push(Box::new(HookChannel::new(hook)));
+    }
+    channels
+}
+
+/// JSON representation of an alert, shared by non-desktop channels
+pub fn alert_json(info: &NotificationInfo) -> serde_json::Value {
+    json!({
+        "provider": info.quota_type.provider,
+        "quota": info.quota_type.quota,
+        "label": info.label,
+        "severity": info.severity,
+        "title": info.title(),
+        "body": info.body(),
+        "projected_percent": info.projected_percent,
+        "reset_time": info.reset_time,
+        "exhaustion_at": info.exhaustion_at,
+        "host": gethostname::gethostname().to_string_lossy(),
+    })
+}
+
+/// Prints alerts to stdout, for journald/syslog or container logs
+pub struct LogChannel;
+
+impl AlertChannel for LogChannel {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    fn send(&self, info: &NotificationInfo) -> Result<()> {
+        println!("[NotifAI] ALERT {}: {}", info.title(), info.body());
+        Ok(())
+    }
+}
+
+/// Appends alerts as JSON lines to a file
+pub struct FileChannel {
+    path: PathBuf,
+}
+
+impl FileChannel {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl AlertChannel for FileChannel {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn send(&self, info: &NotificationInfo) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        writeln!(file, "{}", alert_json(info))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::{NotificationSeverity, QuotaType};
+    use chrono::{Duration, Local};
+
+    #[test]
+    fn test_file_channel_appends_json_lines() {
+        let path =
+            std::env::temp_dir().join(format!("notifai-alerts-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let info = NotificationInfo {
+            quota_type: QuotaType::new("codex", "week"),
+            label: "Codex Weekly limit".to_string(),
+            severity: NotificationSeverity::OverBudget,
+            projected_percent: 130.0,
+            reset_time: Local::now() + Duration::days(2),
+            exhaustion_at: None,
+            digest: Vec::new(),
+            detail: None,
+        };
+        let channel = FileChannel::new(path.clone());
+        channel.send(&info).unwrap();
+        channel.send(&info).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["severity"], "over_budget");
+        assert_eq!(lines[0]["title"], "Codex Weekly limit Over Budget");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,396 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Local};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::attribution::attribute_quotas;
+use crate::metrics::render_metrics;
+use crate::provider::{self, FetchError};
+use crate::tokens::RETENTION_DAYS;
+use crate::AppState;
+
+/// How far back `GET /history` looks when no `from` is given
+const DEFAULT_HISTORY_HOURS: i64 = 24;
+/// Closed periods returned alongside a quota's current period
+const CLOSED_PERIODS_LIMIT: u32 = 10;
+/// Days `GET /tokens` covers when no `days` is given, including today
+const DEFAULT_TOKEN_DAYS: i64 = 7;
+
+/// Starts a refresh in the background; returns false if one is already running
+pub type RefreshTrigger = Arc<dyn Fn() -> bool + Send + Sync>;
+
+/// Response body: JSON for API routes, plain text for Prometheus metrics
+enum Body {
+    Json(Value),
+    Text(String),
+}
+
+impl From<Value> for Body {
+    fn from(value: Value) -> Self {
+        Body::Json(value)
+    }
+}
+
+/// Serve the local HTTP API on 127.0.0.1 from a background thread.
+///
+/// Endpoints: `GET /usage`, `GET /projections`, `GET /history`, `GET /tokens`,
+/// `GET /attribution` and `POST /refresh`,
+/// plus `GET /metrics` when enabled in settings. If `token` is set, requests must send `Authorization: Bearer <token>`.
+pub fn start_api_server(
+    state: Arc<Mutex<AppState>>,
+    port: u16,
+    token: Option<String>,
+    trigger: RefreshTrigger,
+) -> Result<()> {
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(|e| anyhow!("Failed to bind 127.0.0.1:{}: {}", port, e))?;
+    eprintln!("[NotifAI] API listening on http://127.0.0.1:{}", port);
+
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let auth = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Authorization"))
+                .map(|h| h.value.as_str().to_string());
+            let (status, body) = handle(
+                &state,
+                request.method(),
+                request.url(),
+                auth.as_deref(),
+                token.as_deref(),
+                &trigger,
+            );
+
+            let (content_type, body) = match body {
+                Body::Json(value) => ("application/json", value.to_string()),
+                Body::Text(text) => ("text/plain; version=0.0.4", text),
+            };
+            let content_type = Header::from_bytes("Content-Type", content_type).unwrap();
+            let response = Response::from_string(body)
+                .with_status_code(status)
+                .with_header(content_type);
+            if let Err(e) = request.respond(response) {
+                eprintln!("[NotifAI] API response failed: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Route a request and build the status code and body
+fn handle(
+    state: &Arc<Mutex<AppState>>,
+    method: &Method,
+    url: &str,
+    auth: Option<&str>,
+    token: Option<&str>,
+    trigger: &RefreshTrigger,
+) -> (u16, Body) {
+    if let Some(token) = token {
+        if auth != Some(format!("Bearer {}", token).as_str()) {
+            return (401, json!({ "error": "Missing or invalid token" }).into());
+        }
+    }
+
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let (status, body) = match (method, path) {
+        (Method::Get, "/usage") => (200, usage_json(&state.lock().unwrap())),
+        (Method::Get, "/projections") => (200, projections_json(&state.lock().unwrap())),
+        (Method::Get, "/history") => history_json(&state.lock().unwrap(), query),
+        (Method::Get, "/tokens") => tokens_json(&state.lock().unwrap(), query),
+        (Method::Get, "/attribution") => (
+            200,
+            json!({ "attribution": attribute_quotas(&state.lock().unwrap()) }),
+        ),
+        (Method::Get, "/metrics") => {
+            let guard = state.lock().unwrap();
+            if !guard.settings.metrics_enabled {
+                return (404, json!({ "error": "Metrics are disabled" }).into());
+            }
+            return (200, Body::Text(render_metrics(&guard)));
+        }
+        (Method::Post, "/refresh") => {
+            if trigger() {
+                (202, json!({ "refreshing": true }))
+            } else {
+                (409, json!({ "error": "Refresh already in progress" }))
+            }
+        }
+        (
+            _,
+            "/usage" | "/projections" | "/history" | "/tokens" | "/attribution" | "/metrics"
+            | "/refresh",
+        ) => (405, json!({ "error": "Method not allowed" })),
+        _ => (404, json!({ "error": "Not found" })),
+    };
+    (status, body.into())
+}
+
+fn usage_json(state: &AppState) -> Value {
+    let providers: serde_json::Map<String, Value> = state
+        .providers
+        .iter()
+        .map(|(id, p)| (id.clone(), json!(p.usage)))
+        .collect();
+    // Providers whose last fetch failed; their usage above is from `last_success`
+    let fetch_errors: serde_json::Map<String, Value> = state
+        .fetch_stats
+        .iter()
+        .filter_map(|(id, s)| {
+            let error = s.last_error.as_ref()?;
+            Some((
+                id.clone(),
+                json!({
+                    "error": error,
+                    "kind": s.last_cause.as_ref().map(FetchError::kind),
+                    "hint": s.last_cause.as_ref().map(FetchError::hint),
+                    "consecutive_failures": s.consecutive_failures,
+                    "last_success": s.last_success,
+                }),
+            ))
+        })
+        .collect();
+    json!({
+        "last_refresh": state.last_refresh,
+        "providers": providers,
+        "fetch_errors": fetch_errors,
+    })
+}
+
+fn projections_json(state: &AppState) -> Value {
+    // Registry order, so clients get the same order as the tray menu
+    let projections: Vec<Value> = provider::registry(&state.settings)
+        .iter()
+        .filter_map(|p| state.providers.get(p.id()))
+        .map(|p| json!(p.projection))
+        .collect();
+    json!({
+        "last_refresh": state.last_refresh,
+        "projections": projections,
+    })
+}
+
+/// `?provider=..&quota=..` returns the quota's current period and the final
+/// percent of its recent closed periods; otherwise `?from=..&to=..` (RFC 3339,
+/// default last 24h) returns every sample
+fn history_json(state: &AppState, query: &str) -> (u16, Value) {
+    let Some(history) = &state.history else {
+        return (503, json!({ "error": "History is not available" }));
+    };
+
+    let quota = query_param(query, "provider").zip(query_param(query, "quota"));
+    let samples = match &quota {
+        Some((provider, quota)) => history.current_period_samples(provider, quota),
+        None => {
+            let parse = |name: &str| -> Result<Option<DateTime<Local>>, String> {
+                query_param(query, name)
+                    .map(|v| {
+                        DateTime::parse_from_rfc3339(&v)
+                            .map(|t| t.with_timezone(&Local))
+                            .map_err(|e| format!("Invalid {}: {}", name, e))
+                    })
+                    .transpose()
+            };
+            let (from, to) = match (parse("from"), parse("to")) {
+                (Ok(from), Ok(to)) => (from, to),
+                (Err(e), _) | (_, Err(e)) => return (400, json!({ "error": e })),
+            };
+            let to = to.unwrap_or_else(Local::now);
+            let from = from.unwrap_or(to - Duration::hours(DEFAULT_HISTORY_HOURS));
+            history.samples_between(from, to)
+        }
+    };
+    let closed_periods = quota
+        .map(|(provider, quota)| history.closed_periods(&provider, &quota, CLOSED_PERIODS_LIMIT))
+        .transpose();
+
+    match (samples, closed_periods) {
+        (Ok(samples), Ok(None)) => (200, json!({ "samples": samples })),
+        (Ok(samples), Ok(Some(periods))) => (
+            200,
+            json!({ "samples": samples, "closed_periods": periods }),
+        ),
+        (Err(e), _) | (_, Err(e)) => (500, json!({ "error": e.to_string() })),
+    }
+}
+
+/// Token totals per day, project and model from the CLI transcripts, for the
+/// last `?days=..` days (default 7, at most the days kept), with today's total
+/// per provider
+fn tokens_json(state: &AppState, query: &str) -> (u16, Value) {
+    let days = match query_param(query, "days").map(|v| v.parse::<i64>()) {
+        None => DEFAULT_TOKEN_DAYS,
+        Some(Ok(days)) if (1..=RETENTION_DAYS).contains(&days) => days,
+        Some(_) => {
+            let error = format!("days must be between 1 and {}", RETENTION_DAYS);
+            return (400, json!({ "error": error }));
+        }
+    };
+    let now = Local::now();
+    let since = now.date_naive() - Duration::days(days - 1);
+    let pricing = &state.settings.pricing;
+
+    let providers: serde_json::Map<String, Value> = state
+        .transcripts
+        .iter()
+        .map(|(id, scanner)| {
+            let (today, cost) = scanner.ledger.day_total(now.date_naive(), pricing);
+            (
+                id.clone(),
+                json!({
+                    "today": today,
+                    "today_cost_usd": cost,
+                    "rows": scanner.ledger.rows(since, pricing),
+                }),
+            )
+        })
+        .collect();
+    (200, json!({ "since": since, "providers": providers }))
+}
+
+/// Value of a query string parameter, percent-decoded
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| percent_decode(value))
+}
+
+/// Decode `%XX` escapes; `+` is kept literally so raw UTC offsets still parse
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::HistoryStore;
+
+    fn trigger(result: bool) -> RefreshTrigger {
+        Arc::new(move || result)
+    }
+
+    fn request(
+        state: &Arc<Mutex<AppState>>,
+        method: Method,
+        url: &str,
+        auth: Option<&str>,
+        token: Option<&str>,
+    ) -> (u16, Value) {
+        match handle(state, &method, url, auth, token, &trigger(true)) {
+            (status, Body::Json(value)) => (status, value),
+            (status, Body::Text(text)) => (status, Value::String(text)),
+        }
+    }
+
+    fn get(state: &Arc<Mutex<AppState>>, url: &str) -> (u16, Value) {
+        request(state, Method::Get, url, None, None)
+    }
+
+    #[test]
+    fn test_token_required_when_configured() {
+        let state = Arc::new(Mutex::new(AppState::new()));
+        let secret = Some("secret");
+        assert_eq!(request(&state, Method::Get, "/usage", None, secret).0, 401);
+        let wrong = Some("Bearer wrong");
+        assert_eq!(request(&state, Method::Get, "/usage", wrong, secret).0, 401);
+
+        let (status, body) = request(&state, Method::Get, "/usage", Some("Bearer secret"), secret);
+        assert_eq!(status, 200);
+        assert!(body["providers"].as_object().unwrap().is_empty());
+        assert!(body["fetch_errors"].as_object().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_routes() {
+        let state = Arc::new(Mutex::new(AppState::new()));
+        assert_eq!(get(&state, "/projections").0, 200);
+        assert_eq!(get(&state, "/history").0, 503);
+        assert_eq!(get(&state, "/tokens").0, 200);
+        assert_eq!(get(&state, "/tokens?days=0").0, 400);
+        assert_eq!(get(&state, "/tokens?days=30").0, 400);
+        let (status, body) = get(&state, "/attribution");
+        assert_eq!(status, 200);
+        assert!(body["attribution"].as_array().unwrap().is_empty());
+        assert_eq!(get(&state, "/refresh").0, 405);
+        assert_eq!(get(&state, "/nope").0, 404);
+
+        let refresh = |started| {
+            let (status, _) = handle(
+                &state,
+                &Method::Post,
+                "/refresh",
+                None,
+                None,
+                &trigger(started),
+            );
+            status
+        };
+        assert_eq!(refresh(true), 202);
+        assert_eq!(refresh(false), 409);
+    }
+
+    #[test]
+    fn test_metrics_opt_in() {
+        let state = Arc::new(Mutex::new(AppState::new()));
+        assert_eq!(get(&state, "/metrics").0, 404);
+
+        state.lock().unwrap().settings.metrics_enabled = true;
+        let (status, body) = get(&state, "/metrics");
+        assert_eq!(status, 200);
+        assert!(body
+            .as_str()
+            .unwrap()
+            .contains("# TYPE notifai_quota_used_percent gauge"));
+    }
+
+    #[test]
+    fn test_history_range() {
+        let mut app_state = AppState::new();
+        app_state.history = Some(HistoryStore::open_in_memory().unwrap());
+        let state = Arc::new(Mutex::new(app_state));
+
+        let (status, body) = get(&state, "/history");
+        assert_eq!(status, 200);
+        assert!(body["samples"].as_array().unwrap().is_empty());
+
+        let (status, body) = get(&state, "/history?provider=claude&quota=session");
+        assert_eq!(status, 200);
+        assert!(body["closed_periods"].as_array().unwrap().is_empty());
+
+        let (status, _) = get(&state, "/history?from=2024-01-01T00:00:00%2B02:00");
+        assert_eq!(status, 200);
+        let (status, body) = get(&state, "/history?from=yesterday");
+        assert_eq!(status, 400);
+        assert!(body["error"].as_str().unwrap().starts_with("Invalid from"));
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(
+            percent_decode("2024-01-01T00:00:00%2B02:00"),
+            "2024-01-01T00:00:00+02:00"
+        );
+        assert_eq!(percent_decode("week%20all"), "week all");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
@@ -0,0 +1,259 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::history::Sample;
+use crate::projection::PeriodType;
+use crate::provider;
+use crate::tokens::{price_for, ModelPrice, TokenLedger};
+use crate::AppState;
+
+/// Projects listed by name; smaller ones are counted as "other"
+const MAX_PROJECTS: usize = 5;
+
+/// Part of a quota's usage attributed to one project
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectShare {
+    pub project: String,
+    /// Percentage points of the quota
+    pub percent: f32,
+}
+
+/// Which projects used the current period of a quota
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaAttribution {
+    pub provider_id: String,
+    pub provider_name: String,
+    pub quota_id: String,
+    pub label: String,
+    pub used_percent: f32,
+    /// Largest first
+    pub projects: Vec<ProjectShare>,
+    /// Usage without transcript tokens to match (other machines, the web app,
+    /// anything before the first sample) plus the smaller projects
+    pub other_percent: f32,
+}
+
+impl QuotaAttribution {
+    /// e.g. "Week (all models): 57% — repo-a 31%, repo-b 18%, other 8%"
+    pub fn summary(&self) -> String {
+        let mut parts: Vec<String> = self
+            .projects
+            .iter()
+            .map(|p| format!("{} {:.0}%", p.project, p.percent))
+            .collect();
+        if self.other_percent >= 0.5 {
+            parts.push(format!("other {:.0}%", self.other_percent));
+        }
+        if parts.is_empty() {
+            return format!("{}: {:.0}%", self.label, self.used_percent);
+        }
+        format!(
+            "{}: {:.0}% — {}",
+            self.label,
+            self.used_percent,
+            parts.join(", ")
+        )
+    }
+}
+
+/// Model family a quota is limited to, from its id (e.g. "week_sonnet_only")
+fn model_family(quota_id: &str) -> Option<&'static str> {
+    ["sonnet", "opus"]
+        .into_iter()
+        .find(|family| quota_id.contains(family))
+}
+
+/// Split the growth between successive samples of a period among the projects
+/// that logged tokens in between, in proportion to their cost at API prices
+/// (or their token counts when no model has a price). Returns the projects,
+/// largest first, and the rest of the current usage.
+fn attribute(
+    samples: &[Sample],
+    ledger: &TokenLedger,
+    pricing: &[ModelPrice],
+    family: Option<&str>,
+) -> (Vec<ProjectShare>, f32) {
+    let Some(last) = samples.last() else {
+        return (Vec::new(), 0.0);
+    };
+    let used = last.used_percent as f64;
+
+    let mut by_project: HashMap<&str, f64> = HashMap::new();
+    for pair in samples.windows(2) {
+        let growth = (pair[1].used_percent - pair[0].used_percent) as f64;
+        if growth <= 0.0 {
+            continue;
+        }
+        // Cost and tokens per project
+        let mut weights: HashMap<&str, (f64, f64)> = HashMap::new();
+        for entry in ledger.entries_between(pair[0].timestamp, pair[1].timestamp) {
+            if family.is_some_and(|f| !entry.model.contains(f)) {
+                continue;
+            }
+            let cost = price_for(pricing, &entry.model).map_or(0.0, |p| p.cost(&entry.counts));
+            let weight = weights.entry(&entry.project).or_default();
+            weight.0 += cost;
+            weight.1 += entry.counts.total() as f64;
+        }
+        let cost_total: f64 = weights.values().map(|w| w.0).sum();
+        let token_total: f64 = weights.values().map(|w| w.1).sum();
+        for (project, (cost, tokens)) in weights {
+            let share = if cost_total > 0.0 {
+                cost / cost_total
+            } else {
+                tokens / token_total
+            };
+            *by_project.entry(project).or_default() += growth * share;
+        }
+    }
+
+    // Readings that went down (rounding, a partial reset) can leave the growth
+    // above the current usage
+    let attributed: f64 = by_project.values().sum();
+    let scale = if attributed > used {
+        used / attributed
+    } else {
+        1.0
+    };
+    let mut projects: Vec<ProjectShare> = by_project
+        .into_iter()
+        .map(|(project, percent)| ProjectShare {
+            project: project.to_string(),
+            percent: (percent * scale) as f32,
+        })
+        .collect();
+    projects.sort_by(|a, b| {
+        b.percent
+            .total_cmp(&a.percent)
+            .then_with(|| a.project.cmp(&b.project))
+    });
+    projects.truncate(MAX_PROJECTS);
+
+    let listed: f32 = projects.iter().map(|p| p.percent).sum();
+    (projects, (used as f32 - listed).max(0.0))
+}
+
+/// Attribution of the weekly quotas of every provider with transcripts
+pub fn attribute_quotas(state: &AppState) -> Vec<QuotaAttribution> {
+    let Some(history) = &state.history else {
+        return Vec::new();
+    };
+
+    let mut attributions = Vec::new();
+    for provider in provider::registry(&state.settings) {
+        let Some(scanner) = state.transcripts.get(provider.id()) else {
+            continue;
+        };
+        let weekly = provider
+            .quotas()
+            .iter()
+            .filter(|spec| spec.period == PeriodType::Weekly);
+        for spec in weekly {
+            let samples = match history.current_period_samples(provider.id(), spec.id) {
+                Ok(samples) => samples,
+                Err(e) => {
+                    eprintln!("[NotifAI] Failed to load {} history: {}", spec.id, e);
+                    continue;
+                }
+            };
+            let Some(last) = samples.last() else {
+                continue;
+            };
+            let (projects, other_percent) = attribute(
+                &samples,
+                &scanner.ledger,
+                &state.settings.pricing,
+                model_family(spec.id),
+            );
+            attributions.push(QuotaAttribution {
+                provider_id: provider.id().to_string(),
+                provider_name: provider.display_name().to_string(),
+                quota_id: spec.id.to_string(),
+                label: spec.label.to_string(),
+                used_percent: last.used_percent,
+                projects,
+                other_percent,
+            });
+        }
+    }
+    attributions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokens::{default_pricing, TokenCounts, TokenEntry};
+    use chrono::{DateTime, Duration, Local};
+
+    fn sample(at: DateTime<Local>, used_percent: f32) -> Sample {
+        Sample {
+            provider: "claude".to_string(),
+            quota: "week_all_models".to_string(),
+            timestamp: at,
+            used_percent,
+            reset_at: None,
+            period_secs: 7 * 86400,
+        }
+    }
+
+    fn entry(at: DateTime<Local>, project: &str, model: &str, output: u64) -> TokenEntry {
+        TokenEntry {
+            at,
+            project: project.to_string(),
+            model: model.to_string(),
+            counts: TokenCounts {
+                output,
+                ..TokenCounts::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_attribute_growth_by_cost() {
+        let start = Local::now() - Duration::hours(3);
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        let sonnet = "claude-sonnet-4-5-20250929";
+        let mut ledger = TokenLedger::default();
+        // First interval: repo-a four times the cost of repo-b
+        ledger.record(&entry(at(10), "repo-a", sonnet, 400_000));
+        ledger.record(&entry(at(20), "repo-b", sonnet, 100_000));
+        // Second interval: only repo-b, on Opus (5x the Sonnet price)
+        ledger.record(&entry(at(70), "repo-b", "claude-opus-4-1", 10_000));
+        // Nothing logged locally in the third interval
+
+        // 8% before the first sample, then +30, +10, +9
+        let samples = vec![
+            sample(at(0), 8.0),
+            sample(at(60), 38.0),
+            sample(at(120), 48.0),
+            sample(at(180), 57.0),
+        ];
+        let pricing = default_pricing();
+        let (projects, other) = attribute(&samples, &ledger, &pricing, None);
+        let percents: Vec<(&str, f32)> = projects
+            .iter()
+            .map(|p| (p.project.as_str(), p.percent.round()))
+            .collect();
+        assert_eq!(percents, vec![("repo-a", 24.0), ("repo-b", 16.0)]);
+        assert!((other - 17.0).abs() < 0.01);
+
+        let attribution = QuotaAttribution {
+            provider_id: "claude".to_string(),
+            provider_name: "Claude".to_string(),
+            quota_id: "week_all_models".to_string(),
+            label: "Week (all models)".to_string(),
+            used_percent: 57.0,
+            projects,
+            other_percent: other,
+        };
+        assert_eq!(
+            attribution.summary(),
+            "Week (all models): 57% — repo-a 24%, repo-b 16%, other 17%"
+        );
+
+        // The Sonnet-only quota ignores the Opus tokens
+        let (projects, other) = attribute(&samples, &ledger, &pricing, Some("sonnet"));
+        assert_eq!(projects[1].percent.round(), 6.0);
+        assert!((other - 27.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::attribution::{attribute_quotas, QuotaAttribution};
+use crate::headless::{default_data_dir, open_state};
+use crate::notification::NotificationState;
+use crate::projection::{BudgetStatus, QuotaProjection};
+use crate::provider::{self, UsageProvider};
+use crate::usage::UsageData;
+use crate::{refresh, AppState};
+
+/// Exit code when no provider could be fetched
+const EXIT_NO_DATA: i32 = 1;
+/// Exit code when any quota is projected over budget
+const EXIT_OVER_BUDGET: i32 = 2;
+
+/// Command-line options for `notifai status`
+#[derive(Debug, Default, PartialEq)]
+struct StatusOptions {
+    json: bool,
+    /// Directory holding settings.json and history.db (defaults to the app's data dir)
+    data_dir: Option<PathBuf>,
+}
+
+impl StatusOptions {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut opts = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "status" => {}
+                "--json" => opts.json = true,
+                "--data-dir" => {
+                    opts.data_dir = Some(args.next().context("--data-dir needs a path")?.into());
+                }
+                other => bail!("Unknown argument: {}", other),
+            }
+        }
+        Ok(opts)
+    }
+}
+
+/// Machine-readable output of `notifai status --json`
+#[derive(Serialize)]
+struct StatusReport<'a> {
+    fetched_at: DateTime<Local>,
+    worst_status: BudgetStatus,
+    providers: Vec<ProviderReport<'a>>,
+    /// Projects that used each weekly quota, from the CLI transcripts
+    attribution: Vec<QuotaAttribution>,
+}
+
+/// A provider entry; `usage` and `projection` are null if its fetch failed
+#[derive(Serialize)]
+struct ProviderReport<'a> {
+    id: &'a str,
+    name: &'a str,
+    usage: Option<&'a UsageData>,
+    projection: Option<&'a QuotaProjection>,
+}
+
+/// Human-readable output mirroring the tray menu
+fn render_table(providers: &[Box<dyn UsageProvider>], state: &AppState) -> String {
+    let attributions = attribute_quotas(state);
+    let mut out = String::new();
+    for provider in providers {
+        out.push_str(provider.display_name());
+        out.push('\n');
+        match state.providers.get(provider.id()) {
+            Some(provider_state) => {
+                for quota in &provider_state.projection.quotas {
+                    out.push_str(&format!("  {}\n", quota.summary()));
+                }
+                for line in provider.extra_lines(&provider_state.usage) {
+                    out.push_str(&format!("  {}\n", line));
+                }
+                for attribution in attributions.iter().filter(|a| a.provider_id == provider.id()) {
+                    out.push_str(&format!("  {}\n", attribution.summary()));
+                }
+            }
+            None => {
+                let stats = state.fetch_stats.get(provider.id());
+                match stats.and_then(|s| s.last_error.as_ref()) {
+                    Some(error) => out.push_str(&format!("  unavailable: {}\n", error)),
+                    None => out.push_str("  unavailable (fetch failed)\n"),
+                }
+                if let Some(cause) = stats.and_then(|s| s.last_cause.as_ref()) {
+                    out.push_str(&format!("  {}\n", cause.hint()));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// One-shot fetch printing a table (or JSON with `--json`). Returns the process
+/// exit code: 0 normally, 1 if nothing could be fetched, 2 if any quota is over budget.
+pub fn run_status(args: Vec<String>) -> Result<i32> {
+    let opts = StatusOptions::parse(args)?;
+    let data_dir = opts
+        .data_dir
+        .or_else(default_data_dir)
+        .context("Could not resolve data dir, pass --data-dir")?;
+
+    let state = open_state(&data_dir);
+    let notif_state = Arc::new(Mutex::new(NotificationState::new()));
+    // No alert channels: status only reports
+    let worst_status = refresh(&state, &notif_state, &[]);
+
+    let guard = state.lock().unwrap();
+    let providers = provider::registry(&guard.settings);
+    if opts.json {
+        let report = StatusReport {
+            fetched_at: guard.last_refresh.unwrap_or_else(Local::now),
+            worst_status: worst_status.unwrap_or(BudgetStatus::Unknown),
+            providers: providers
+                .iter()
+                .map(|p| {
+                    let provider_state = guard.providers.get(p.id());
+                    ProviderReport {
+                        id: p.id(),
+                        name: p.display_name(),
+                        usage: provider_state.map(|s| &s.usage),
+                        projection: provider_state.map(|s| &s.projection),
+                    }
+                })
+                .collect(),
+            attribution: attribute_quotas(&guard),
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", render_table(&providers, &guard));
+    }
+
+    Ok(match worst_status {
+        None => EXIT_NO_DATA,
+        Some(BudgetStatus::OverBudget) => EXIT_OVER_BUDGET,
+        Some(_) => 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projection::{ProjectedQuota, ProjectedUsage, ProjectionMethod};
+    use crate::settings::Settings;
+    use crate::provider::FetchError;
+    use crate::{FetchStats, ProviderState};
+
+    #[test]
+    fn test_parse_options() {
+        let args = ["status", "--json", "--data-dir", "/tmp/notifai"].map(String::from);
+        let opts = StatusOptions::parse(args).unwrap();
+        assert!(opts.json);
+        assert_eq!(opts.data_dir, Some(PathBuf::from("/tmp/notifai")));
+        assert!(StatusOptions::parse(["status", "--yaml"].map(String::from)).is_err());
+    }
+
+    #[test]
+    fn test_render_table() {
+        let mut state = AppState::new();
+        state.providers.insert(
+            "claude".to_string(),
+            ProviderState {
+                usage: UsageData::new(),
+                projection: QuotaProjection {
+                    provider_id: "claude".to_string(),
+                    provider_name: "Claude".to_string(),
+                    quotas: vec![ProjectedQuota {
+                        id: "session".to_string(),
+                        label: "Session".to_string(),
+                        projected: ProjectedUsage {
+                            current_percent: 40.0,
+                            projected_percent: 95.0,
+                            status: BudgetStatus::OnTrack,
+                            time_remaining_secs: 2 * 3600 + 600,
+                            reset_at: Local::now() + chrono::Duration::seconds(2 * 3600 + 600),
+                            method: ProjectionMethod::Linear,
+                            exhaustion_at: None,
+                        },
+                    }],
+                },
+            },
+        );
+
+        let table = render_table(&provider::registry(&Settings::default()), &state);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "Claude");
+        assert_eq!(lines[1], "  ◐ Session: 40% → 95% (resets in 2h 10m)");
+        assert_eq!(lines[2], "  Extra usage: not enabled");
+        assert_eq!(lines[3], "Codex");
+        assert_eq!(lines[4], "  unavailable (fetch failed)");
+
+        let cause = FetchError::NotLoggedIn {
+            login_command: "codex login",
+        };
+        state.fetch_stats.insert(
+            "codex".to_string(),
+            FetchStats {
+                last_error: Some(cause.to_string()),
+                last_cause: Some(cause),
+                ..FetchStats::default()
+            },
+        );
+        let table = render_table(&provider::registry(&Settings::default()), &state);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[4], "  unavailable: Not logged in or session expired");
+        assert_eq!(lines[5], "  Run `codex login`");
+    }
+}
@@ -1,22 +1,35 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{Context, Result};
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
 use regex::Regex;
 use std::io::{Read, Write};
 use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant};
-use strip_ansi_escapes;
 
-use crate::usage::UsageData;
+use crate::projection::PeriodType;
+use crate::provider::{FetchError, QuotaSpec, UsageProvider};
+use crate::terminal::VirtualTerminal;
+use crate::transcripts::{codex_sessions_dir, CodexFormat, TranscriptScanner};
+use crate::usage::{Quota, UsageData};
 
-/// Run `codex` and issue `/status`, capturing the output.
+/// PTY size; wide enough that the /status limit bars don't wrap
+const PTY_ROWS: u16 = 40;
+const PTY_COLS: u16 = 120;
+
+/// Give up waiting for the prompt and `/status` after this long
+const TIMEOUT_SECS: u64 = 45;
+
+/// Suggested when Codex isn't logged in
+const LOGIN_COMMAND: &str = "codex login";
+
+/// Run `codex` and issue `/status`, returning the rendered screen.
 fn run_codex_status(codex_path: &str) -> Result<String> {
     let pty_system = NativePtySystem::default();
 
     let pair = pty_system
         .openpty(PtySize {
-            rows: 40,
-            cols: 120,
+            rows: PTY_ROWS,
+            cols: PTY_COLS,
             pixel_width: 0,
             pixel_height: 0,
         })
@@ -32,7 +45,7 @@ fn run_codex_status(codex_path: &str) -> Result<String> {
     let mut child = pair
         .slave
         .spawn_command(cmd)
-        .context("Failed to spawn codex")?;
+        .map_err(|e| FetchError::from_spawn(&cli_path, e))?;
 
     // Drop slave side
     drop(pair.slave);
@@ -64,13 +77,13 @@ fn run_codex_status(codex_path: &str) -> Result<String> {
         }
     });
 
-    let mut output = String::new();
+    let mut terminal = VirtualTerminal::new(PTY_ROWS, PTY_COLS);
 
     let start = Instant::now();
-    let timeout = Duration::from_secs(45);
-    let mut current_screen = String::new();
+    let timeout = Duration::from_secs(TIMEOUT_SECS);
     let mut resent_command = false;
     let mut sent_status = false;
+    let mut timed_out = false;
 
     loop {
         if start.elapsed() > timeout {
@@ -78,18 +91,18 @@ fn run_codex_status(codex_path: &str) -> Result<String> {
                 "[NotifAI] Codex status: timeout after {:?}",
                 start.elapsed()
             );
+            timed_out = true;
             break;
         }
 
         match rx.recv_timeout(Duration::from_millis(100)) {
             Ok(Some(bytes)) => {
                 let chunk = String::from_utf8_lossy(&bytes);
-                output.push_str(&chunk);
-                current_screen.push_str(&chunk);
+                terminal.feed(&bytes);
 
                 // Respond to terminal capability queries
                 if chunk.contains("\u{1b}[6n") {
-                    let _ = writer.write_all(b"\x1b[1;1R");
+                    let _ = writer.write_all(&terminal.cursor_position_report());
                     writer.flush().ok();
                     eprintln!("[NotifAI] Codex: replied to cursor position query");
                 }
@@ -100,9 +113,8 @@ fn run_codex_status(codex_path: &str) -> Result<String> {
                     eprintln!("[NotifAI] Codex: replied to device attributes query");
                 }
 
-                // Strip ANSI for detection
-                let stripped = strip_ansi_escapes::strip(&current_screen);
-                let clean = String::from_utf8_lossy(&stripped);
+                // Detect state on the rendered screen
+                let clean = terminal.contents();
 
                 // Detect ready prompt then send /status once
                 if !sent_status
@@ -131,9 +143,19 @@ fn run_codex_status(codex_path: &str) -> Result<String> {
 
                 if has_five && has_week && has_left {
                     // Good enough snapshot
-                    output = clean.to_string();
                     break;
                 }
+
+                // A login prompt waits for input, so stop waiting
+                if !has_left {
+                    if let Some(error @ FetchError::NotLoggedIn { .. }) =
+                        FetchError::from_screen(&clean, LOGIN_COMMAND)
+                    {
+                        eprintln!("[NotifAI] Codex: {}", error);
+                        let _ = child.kill();
+                        return Err(error.into());
+                    }
+                }
             }
             Ok(None) => break,
             Err(mpsc::RecvTimeoutError::Timeout) => { /* continue loop for timeout/resend checks */
@@ -160,16 +182,20 @@ fn run_codex_status(codex_path: &str) -> Result<String> {
 
     let _ = child.kill();
 
-    Ok(output)
+    let screen = terminal.contents();
+    if timed_out && !screen.contains("% left") {
+        return Err(FetchError::Timeout { secs: TIMEOUT_SECS }.into());
+    }
+    Ok(screen)
 }
 
-/// Parse Codex /status output into UsageData codex fields.
-fn parse_codex_output(raw_output: &str) -> Result<UsageData> {
-    if raw_output
+/// Parse Codex /status output into UsageData quotas.
+fn parse_codex_output(output: &str) -> Result<UsageData> {
+    if output
         .to_lowercase()
         .contains("cursor position could not be read")
     {
-        bail!("Codex CLI failed to read cursor position (terminal emulation)");
+        return Err(FetchError::TerminalUnsupported.into());
     }
 
     let mut data = UsageData::new();
@@ -181,44 +207,48 @@ fn parse_codex_output(raw_output: &str) -> Result<UsageData> {
         Regex::new(r"(?i)(5h limit|weekly limit):.*?(\d+)%\s+left\s*\(resets\s+([^\)]+)\)")?;
 
     let mut seen_any = false;
-    for caps in line_re.captures_iter(raw_output) {
-        let label = caps
-            .get(1)
-            .map(|m| m.as_str().to_lowercase())
-            .unwrap_or_default();
+    for caps in line_re.captures_iter(output) {
+        let label = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
         let left_pct: f32 = caps
             .get(2)
             .and_then(|m| m.as_str().parse().ok())
             .unwrap_or(0.0);
         let reset = caps.get(3).map(|m| m.as_str().trim().to_string());
 
-        match label.as_str() {
-            "5h limit" => {
-                data.codex_five_hour_left = Some(left_pct);
-                data.codex_five_hour_reset = reset;
-            }
-            "weekly limit" => {
-                data.codex_week_left = Some(left_pct);
-                data.codex_week_reset = reset;
-            }
-            _ => {}
-        }
+        let Some(spec) = CODEX_QUOTAS
+            .iter()
+            .find(|spec| spec.label.eq_ignore_ascii_case(label))
+        else {
+            continue;
+        };
+
+        // Codex reports "left", convert to "used"
+        data.quotas.push(Quota {
+            id: spec.id.to_string(),
+            label: spec.label.to_string(),
+            used_percent: 100.0 - left_pct,
+            reset,
+            period: spec.period,
+        });
         seen_any = true;
     }
 
     if seen_any {
         eprintln!(
-            "[NotifAI] Codex parsed: five_hour_left={:?} reset={:?}, week_left={:?} reset={:?}",
-            data.codex_five_hour_left,
-            data.codex_five_hour_reset,
-            data.codex_week_left,
-            data.codex_week_reset
+            "[NotifAI] Codex parsed: {:?}",
+            data.quotas
+                .iter()
+                .map(|q| (&q.id, q.used_percent, &q.reset))
+                .collect::<Vec<_>>()
         );
     } else {
         eprintln!(
-            "[NotifAI] Codex parse found no matches. Raw (first 300 chars): {:?}",
-            raw_output.chars().take(300).collect::<String>()
+            "[NotifAI] Codex parse found no matches. Screen (first 300 chars): {:?}",
+            output.chars().take(300).collect::<String>()
         );
+        let error = FetchError::from_screen(output, LOGIN_COMMAND)
+            .unwrap_or(FetchError::UnrecognizedOutput);
+        return Err(error.into());
     }
 
     Ok(data)
@@ -226,8 +256,58 @@ fn parse_codex_output(raw_output: &str) -> Result<UsageData> {
 
 /// Fetch Codex usage limits.
 pub fn fetch_codex_usage(codex_path: &str) -> Result<UsageData> {
-    let raw = run_codex_status(codex_path)?;
-    parse_codex_output(&raw)
+    let screen = run_codex_status(codex_path)?;
+    parse_codex_output(&screen)
+}
+
+/// Codex quotas, as reported by `/status`
+const CODEX_QUOTAS: [QuotaSpec; 2] = [
+    QuotaSpec {
+        id: "five_hour",
+        label: "5h limit",
+        period: PeriodType::Session,
+    },
+    QuotaSpec {
+        id: "week",
+        label: "Weekly limit",
+        period: PeriodType::Weekly,
+    },
+];
+
+/// Codex (OpenAI) usage provider (drives `codex` + `/status`)
+pub struct CodexProvider {
+    codex_path: String,
+}
+
+impl CodexProvider {
+    pub fn new(codex_path: &str) -> Self {
+        Self {
+            codex_path: codex_path.to_string(),
+        }
+    }
+}
+
+impl UsageProvider for CodexProvider {
+    fn id(&self) -> &str {
+        "codex"
+    }
+
+    fn display_name(&self) -> &str {
+        "Codex"
+    }
+
+    fn quotas(&self) -> &[QuotaSpec] {
+        &CODEX_QUOTAS
+    }
+
+    fn fetch(&self) -> Result<UsageData> {
+        fetch_codex_usage(&self.codex_path)
+    }
+
+    fn transcripts(&self) -> Option<TranscriptScanner> {
+        let dir = codex_sessions_dir()?;
+        Some(TranscriptScanner::new(dir, Box::new(CodexFormat::default())))
+    }
 }
 
 #[cfg(test)]
@@ -238,9 +318,42 @@ mod tests {
     fn parses_codex_status_lines() {
         let sample = "5h limit:         [████████████████████] 99% left (resets 13:35)\nWeekly limit:     [████████████████░░░░] 80% left (resets 13:17)\n";
         let data = parse_codex_output(sample).unwrap();
-        assert_eq!(data.codex_five_hour_left, Some(99.0));
-        assert_eq!(data.codex_five_hour_reset.as_deref(), Some("13:35"));
-        assert_eq!(data.codex_week_left, Some(80.0));
-        assert_eq!(data.codex_week_reset.as_deref(), Some("13:17"));
+
+        assert_eq!(data.quotas.len(), 2);
+
+        let five_hour = &data.quotas[0];
+        assert_eq!(five_hour.id, "five_hour");
+        assert_eq!(five_hour.used_percent, 1.0);
+        assert_eq!(five_hour.reset.as_deref(), Some("13:35"));
+
+        let week = &data.quotas[1];
+        assert_eq!(week.id, "week");
+        assert_eq!(week.used_percent, 20.0);
+        assert_eq!(week.reset.as_deref(), Some("13:17"));
+    }
+
+    #[test]
+    fn classifies_screens_without_limits() {
+        let login = "Welcome to Codex\n> 1. Sign in with ChatGPT\n  2. Provide your own API key\n";
+        let error = parse_codex_output(login).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<FetchError>(),
+            Some(&FetchError::NotLoggedIn {
+                login_command: "codex login"
+            })
+        );
+
+        let error = parse_codex_output("/status\nSomething new\n").unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<FetchError>(),
+            Some(&FetchError::UnrecognizedOutput)
+        );
+
+        let cursor = "Error: The cursor position could not be read within a normal duration\n";
+        let error = parse_codex_output(cursor).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<FetchError>(),
+            Some(&FetchError::TerminalUnsupported)
+        );
     }
 }
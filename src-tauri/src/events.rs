@@ -0,0 +1,256 @@
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+
+use crate::notification::{NotificationInfo, NotificationSeverity, QuotaType};
+use crate::projection::{BudgetStatus, QuotaProjection};
+use crate::usage::UsageData;
+
+/// Minimum forward jump of the reset time that counts as a new period, so that
+/// re-parsing the same reset string a minute apart isn't mistaken for a reset
+const RESET_JUMP_MIN_MINUTES: i64 = 30;
+
+/// Something that happened to a quota, passed to hooks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum QuotaEvent {
+    /// An alert was sent
+    Notification {
+        provider: String,
+        quota: String,
+        label: String,
+        severity: NotificationSeverity,
+        title: String,
+        body: String,
+        projected_percent: f32,
+        reset_time: DateTime<Local>,
+        exhaustion_at: Option<DateTime<Local>>,
+    },
+    /// The quota period rolled over since the previous fetch
+    Reset {
+        provider: String,
+        quota: String,
+        label: String,
+        /// Last reading of the closed period
+        previous_percent: f32,
+        used_percent: f32,
+        /// When the closed period ended
+        previous_reset_time: DateTime<Local>,
+        reset_time: DateTime<Local>,
+    },
+    /// The budget status (tray color) of the quota changed
+    StatusChange {
+        provider: String,
+        quota: String,
+        label: String,
+        previous_status: BudgetStatus,
+        status: BudgetStatus,
+        projected_percent: f32,
+    },
+}
+
+impl QuotaEvent {
+    /// Event name, e.g. "status_change"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            QuotaEvent::Notification { .. } => "notification",
+            QuotaEvent::Reset { .. } => "reset",
+            QuotaEvent::StatusChange { .. } => "status_change",
+        }
+    }
+
+    /// "Quota refreshed" alert for a reset event, labeled like other alerts
+    /// (e.g. "Claude Session")
+    pub fn reset_notification(&self, provider_name: &str) -> Option<NotificationInfo> {
+        let QuotaEvent::Reset {
+            provider,
+            quota,
+            label,
+            used_percent,
+            reset_time,
+            ..
+        } = self
+        else {
+            return None;
+        };
+        Some(NotificationInfo {
+            quota_type: QuotaType::new(provider, quota),
+            label: format!("{} {}", provider_name, label),
+            severity: NotificationSeverity::Refreshed,
+            projected_percent: *used_percent,
+            reset_time: *reset_time,
+            exhaustion_at: None,
+            digest: Vec::new(),
+            detail: None,
+        })
+    }
+}
+
+impl From<&NotificationInfo> for QuotaEvent {
+    fn from(info: &NotificationInfo) -> Self {
+        QuotaEvent::Notification {
+            provider: info.quota_type.provider.clone(),
+            quota: info.quota_type.quota.clone(),
+            label: info.label.clone(),
+            severity: info.severity.clone(),
+            title: info.title(),
+            body: info.body(),
+            projected_percent: info.projected_percent,
+            reset_time: info.reset_time,
+            exhaustion_at: info.exhaustion_at,
+        }
+    }
+}
+
+/// Compare two successive fetches of a provider and report resets and status
+/// changes. Quotas missing from either fetch are ignored.
+pub fn detect_events(
+    previous: (&UsageData, &QuotaProjection),
+    current: (&UsageData, &QuotaProjection),
+) -> Vec<QuotaEvent> {
+    let (prev_usage, prev_projection) = previous;
+    let (usage, projection) = current;
+    let provider = &projection.provider_id;
+    let mut events = Vec::new();
+
+    for quota in &usage.quotas {
+        let Some(prev) = prev_usage.quotas.iter().find(|q| q.id == quota.id) else {
+            continue;
+        };
+        // Instants parsed at each fetch: re-parsing the previous reset string now
+        // would move a reset that has passed to the next day
+        let reset_at = |projection: &QuotaProjection| {
+            projection
+                .quotas
+                .iter()
+                .find(|q| q.id == quota.id)
+                .map(|q| q.projected.reset_at)
+        };
+        let (Some(prev_reset_time), Some(reset_time)) =
+            (reset_at(prev_projection), reset_at(projection))
+        else {
+            continue;
+        };
+        let jumped = reset_time - prev_reset_time >= Duration::minutes(RESET_JUMP_MIN_MINUTES);
+        if jumped && quota.used_percent < prev.used_percent {
+            events.push(QuotaEvent::Reset {
+                provider: provider.clone(),
+                quota: quota.id.clone(),
+                label: quota.label.clone(),
+                previous_percent: prev.used_percent,
+                used_percent: quota.used_percent,
+                previous_reset_time: prev_reset_time,
+                reset_time,
+            });
+        }
+    }
+
+    for quota in &projection.quotas {
+        let Some(prev) = prev_projection.quotas.iter().find(|q| q.id == quota.id) else {
+            continue;
+        };
+        if prev.projected.status != quota.projected.status {
+            events.push(QuotaEvent::StatusChange {
+                provider: provider.clone(),
+                quota: quota.id.clone(),
+                label: quota.label.clone(),
+                previous_status: prev.projected.status,
+                status: quota.projected.status,
+                projected_percent: quota.projected.projected_percent,
+            });
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projection::{PeriodType, ProjectedQuota, ProjectedUsage, ProjectionMethod};
+    use crate::usage::Quota;
+
+    fn fetch(
+        percent: f32,
+        reset: DateTime<Local>,
+        status: BudgetStatus,
+    ) -> (UsageData, QuotaProjection) {
+        let mut usage = UsageData::new();
+        usage.quotas.push(Quota {
+            id: "five_hour".to_string(),
+            label: "5h limit".to_string(),
+            used_percent: percent,
+            reset: Some(reset.format("%H:%M").to_string()),
+            period: PeriodType::Session,
+        });
+        let projection = QuotaProjection {
+            provider_id: "codex".to_string(),
+            provider_name: "Codex".to_string(),
+            quotas: vec![ProjectedQuota {
+                id: "five_hour".to_string(),
+                label: "5h limit".to_string(),
+                projected: ProjectedUsage {
+                    current_percent: percent,
+                    projected_percent: percent,
+                    status,
+                    time_remaining_secs: 3600,
+                    reset_at: reset,
+                    method: ProjectionMethod::Linear,
+                    exhaustion_at: None,
+                },
+            }],
+        };
+        (usage, projection)
+    }
+
+    #[test]
+    fn test_detects_reset_and_status_change() {
+        let now = Local::now();
+        let (u1, p1) = fetch(92.0, now + Duration::minutes(10), BudgetStatus::OverBudget);
+        let (u2, p2) = fetch(3.0, now + Duration::hours(4), BudgetStatus::UnderBudget);
+
+        let events = detect_events((&u1, &p1), (&u2, &p2));
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind(), "reset");
+        assert!(matches!(
+            events[0],
+            QuotaEvent::Reset { previous_percent, used_percent, .. }
+                if previous_percent == 92.0 && used_percent == 3.0
+        ));
+        let info = events[0].reset_notification("Codex").unwrap();
+        assert_eq!(info.title(), "Codex 5h limit Quota Refreshed");
+        assert!(info.body().starts_with("3% used, next reset at "));
+        assert!(events[1].reset_notification("Codex").is_none());
+        assert!(matches!(
+            events[1],
+            QuotaEvent::StatusChange {
+                previous_status: BudgetStatus::OverBudget,
+                status: BudgetStatus::UnderBudget,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_detects_reset_after_it_passed() {
+        let now = Local::now();
+        // The previous reset is behind us by the time the next fetch arrives
+        let previous_reset = now - Duration::minutes(5);
+        let (u1, p1) = fetch(88.0, previous_reset, BudgetStatus::OnTrack);
+        let (u2, p2) = fetch(1.0, now + Duration::hours(5), BudgetStatus::OnTrack);
+
+        let events = detect_events((&u1, &p1), (&u2, &p2));
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            QuotaEvent::Reset { previous_reset_time, .. } if previous_reset_time == previous_reset
+        ));
+    }
+
+    #[test]
+    fn test_steady_usage_has_no_events() {
+        let reset = Local::now() + Duration::hours(2);
+        let (u1, p1) = fetch(40.0, reset, BudgetStatus::OnTrack);
+        let (u2, p2) = fetch(45.0, reset, BudgetStatus::OnTrack);
+        assert!(detect_events((&u1, &p1), (&u2, &p2)).is_empty());
+    }
+}
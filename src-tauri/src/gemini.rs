@@ -0,0 +1,403 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
+use regex::Regex;
+use std::io::{Read, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::projection::PeriodType;
+use crate::provider::{FetchError, QuotaSpec, UsageProvider};
+use crate::terminal::VirtualTerminal;
+use crate::usage::{slugify, Quota, UsageData};
+
+/// PTY size; wide enough that the /stats model table doesn't wrap
+const PTY_ROWS: u16 = 50;
+const PTY_COLS: u16 = 120;
+
+/// Give up waiting for the prompt and `/stats` after this long
+const TIMEOUT_SECS: u64 = 45;
+
+/// Once the model table is up, wait this long for the quota column to render
+const TABLE_SETTLE: Duration = Duration::from_secs(3);
+
+/// Suggested when the Gemini CLI isn't logged in; it asks how to sign in on start
+const LOGIN_COMMAND: &str = "gemini";
+
+/// Run `gemini` and issue `/stats`, returning the rendered screen.
+fn run_gemini_stats(gemini_path: &str) -> Result<String> {
+    let pty_system = NativePtySystem::default();
+
+    let pair = pty_system
+        .openpty(PtySize {
+            rows: PTY_ROWS,
+            cols: PTY_COLS,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .context("Failed to open PTY")?;
+
+    // Locate binary (explicit path parameter, with env fallback)
+    let cli_path = std::env::var("GEMINI_PATH").unwrap_or_else(|_| gemini_path.to_string());
+    eprintln!("[NotifAI] Gemini: using binary path {}", cli_path);
+    let mut cmd = CommandBuilder::new(&cli_path);
+    cmd.env("TERM", "xterm-256color");
+
+    let mut child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| FetchError::from_spawn(&cli_path, e))?;
+
+    drop(pair.slave);
+
+    let mut writer = pair.master.take_writer()?;
+
+    // Blocking reader on its own thread, consumed through a channel with a timeout
+    let reader = pair.master.try_clone_reader()?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut reader = reader;
+        let mut buffer = [0u8; 4096];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => {
+                    let _ = tx.send(None);
+                    break;
+                }
+                Ok(n) => {
+                    let _ = tx.send(Some(buffer[..n].to_vec()));
+                }
+                Err(e) => {
+                    eprintln!("[NotifAI] Gemini read error thread: {}", e);
+                    let _ = tx.send(None);
+                    break;
+                }
+            }
+        }
+    });
+
+    let mut terminal = VirtualTerminal::new(PTY_ROWS, PTY_COLS);
+
+    let start = Instant::now();
+    let timeout = Duration::from_secs(TIMEOUT_SECS);
+    let mut sent_stats = false;
+    let mut table_seen_at: Option<Instant> = None;
+    let mut timed_out = false;
+
+    loop {
+        if start.elapsed() > timeout {
+            eprintln!(
+                "[NotifAI] Gemini stats: timeout after {:?}",
+                start.elapsed()
+            );
+            timed_out = true;
+            break;
+        }
+
+        match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(Some(bytes)) => {
+                let chunk = String::from_utf8_lossy(&bytes);
+                terminal.feed(&bytes);
+
+                // Respond to terminal capability queries
+                if chunk.contains("\u{1b}[6n") {
+                    let _ = writer.write_all(&terminal.cursor_position_report());
+                    writer.flush().ok();
+                }
+
+                let clean = terminal.contents();
+
+                // Input box is up: ask for the stats panel once
+                if !sent_stats
+                    && (clean.contains("Type your message") || clean.contains("context left"))
+                {
+                    let _ = writer.write_all(b"/stats\r");
+                    writer.flush().ok();
+                    sent_stats = true;
+                    eprintln!("[NotifAI] Gemini: prompt ready, sent /stats");
+                }
+
+                if sent_stats && clean.to_lowercase().contains("resets in") {
+                    break;
+                }
+                if sent_stats && table_seen_at.is_none() && clean.contains("Model Usage") {
+                    table_seen_at = Some(Instant::now());
+                }
+
+                // A login prompt waits for input, so stop waiting
+                if !sent_stats {
+                    if let Some(error @ FetchError::NotLoggedIn { .. }) =
+                        FetchError::from_screen(&clean, LOGIN_COMMAND)
+                    {
+                        eprintln!("[NotifAI] Gemini: {}", error);
+                        let _ = child.kill();
+                        return Err(error.into());
+                    }
+                }
+            }
+            Ok(None) => break,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(e) => {
+                eprintln!("[NotifAI] Gemini channel error: {}", e);
+                break;
+            }
+        }
+
+        // Without daily limits (e.g. API key auth) the table has no quota column
+        if table_seen_at.is_some_and(|at| at.elapsed() > TABLE_SETTLE) {
+            break;
+        }
+
+        if let Ok(Some(_status)) = child.try_wait() {
+            break;
+        }
+    }
+
+    let _ = child.kill();
+
+    let screen = terminal.contents();
+    if timed_out && !screen.to_lowercase().contains("resets in") {
+        return Err(FetchError::Timeout { secs: TIMEOUT_SECS }.into());
+    }
+    Ok(screen)
+}
+
+/// Turn "23h 12m" (or "1d 2h", "45m") into a duration
+fn parse_relative(text: &str) -> Option<ChronoDuration> {
+    let part_re = Regex::new(r"(\d+)\s*([dhm])").ok()?;
+    let mut total = ChronoDuration::zero();
+    let mut any = false;
+    for caps in part_re.captures_iter(text) {
+        let value: i64 = caps[1].parse().ok()?;
+        total += match &caps[2] {
+            "d" => ChronoDuration::days(value),
+            "h" => ChronoDuration::hours(value),
+            _ => ChronoDuration::minutes(value),
+        };
+        any = true;
+    }
+    any.then_some(total)
+}
+
+/// Reset instants are rounded to this many seconds, see `reset_clock_time`
+const RESET_ROUNDING_SECS: i64 = 15 * 60;
+
+/// The local date and 24h time of a relative reset, rounded to the nearest 15
+/// minutes. The CLI counts down to the minute, so `now + remaining` drifts a
+/// little between refreshes; rounding keeps it on one value for the whole
+/// period. The date keeps a reset almost 24h away from reading as today.
+fn reset_clock_time(now: DateTime<Local>, remaining: ChronoDuration) -> Option<String> {
+    let at = (now + remaining).timestamp();
+    let rounded =
+        (at + RESET_ROUNDING_SECS / 2).div_euclid(RESET_ROUNDING_SECS) * RESET_ROUNDING_SECS;
+    let reset = DateTime::from_timestamp(rounded, 0)?.with_timezone(&Local);
+    Some(reset.format("%Y-%m-%d %H:%M").to_string())
+}
+
+/// Parse the `/stats` model table into daily quotas. The CLI prints "Usage left"
+/// per model with a relative reset ("Resets in 23h 12m"), which is stored as a
+/// rounded local date and time (see `reset_clock_time`).
+fn parse_gemini_output(output: &str, now: DateTime<Local>) -> Result<UsageData> {
+    let mut data = UsageData::new();
+
+    // Example line:
+    // gemini-2.5-pro      3      98.5% (Resets in 23h 12m)
+    let line_re = Regex::new(
+        r"(?i)(gemini-[\w.\-]+)\s+(?:[\d,]+|-)?\s*(\d+(?:\.\d+)?)%\s*\(resets in\s+([^)]+)\)",
+    )?;
+
+    for caps in line_re.captures_iter(output) {
+        let model = &caps[1];
+        let left_pct: f32 = caps[2].parse().unwrap_or(100.0);
+        let reset = parse_relative(&caps[3]).and_then(|d| reset_clock_time(now, d));
+
+        let id = format!("daily_{}", slugify(model));
+        let label = GEMINI_QUOTAS.iter().find(|spec| spec.id == id).map_or_else(
+            || format!("Daily ({})", model),
+            |spec| spec.label.to_string(),
+        );
+        if data.quotas.iter().any(|q| q.id == id) {
+            continue;
+        }
+
+        // Gemini reports "left", convert to "used"
+        data.quotas.push(Quota {
+            id,
+            label,
+            used_percent: 100.0 - left_pct,
+            reset,
+            period: PeriodType::Daily,
+        });
+    }
+
+    if data.quotas.is_empty() {
+        eprintln!(
+            "[NotifAI] Gemini parse found no matches. Screen (first 300 chars): {:?}",
+            output.chars().take(300).collect::<String>()
+        );
+        let error = FetchError::from_screen(output, LOGIN_COMMAND)
+            .unwrap_or(FetchError::UnrecognizedOutput);
+        return Err(error.into());
+    }
+
+    eprintln!(
+        "[NotifAI] Gemini parsed: {:?}",
+        data.quotas
+            .iter()
+            .map(|q| (&q.id, q.used_percent, &q.reset))
+            .collect::<Vec<_>>()
+    );
+    Ok(data)
+}
+
+/// Fetch Gemini CLI daily limits.
+pub fn fetch_gemini_usage(gemini_path: &str) -> Result<UsageData> {
+    let screen = run_gemini_stats(gemini_path)?;
+    parse_gemini_output(&screen, Local::now())
+}
+
+/// Gemini models with daily limits known to appear in `/stats`; other models are
+/// picked up dynamically
+const GEMINI_QUOTAS: [QuotaSpec; 2] = [
+    QuotaSpec {
+        id: "daily_gemini_2_5_pro",
+        label: "Daily (2.5 Pro)",
+        period: PeriodType::Daily,
+    },
+    QuotaSpec {
+        id: "daily_gemini_2_5_flash",
+        label: "Daily (2.5 Flash)",
+        period: PeriodType::Daily,
+    },
+];
+
+/// Gemini CLI usage provider (drives `gemini` + `/stats`)
+pub struct GeminiProvider {
+    gemini_path: String,
+}
+
+impl GeminiProvider {
+    pub fn new(gemini_path: &str) -> Self {
+        Self {
+            gemini_path: gemini_path.to_string(),
+        }
+    }
+}
+
+impl UsageProvider for GeminiProvider {
+    fn id(&self) -> &str {
+        "gemini"
+    }
+
+    fn display_name(&self) -> &str {
+        "Gemini"
+    }
+
+    fn quotas(&self) -> &[QuotaSpec] {
+        &GEMINI_QUOTAS
+    }
+
+    fn fetch(&self) -> Result<UsageData> {
+        fetch_gemini_usage(&self.gemini_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projection::parse_reset_time;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parses_gemini_stats_table() {
+        let sample = "\
+│  Model Usage                  Reqs                  Usage left      │
+│  ───────────────────────────────────────────────────────────────    │
+│  gemini-2.5-flash                -      100.0% (Resets in 23h 59m)  │
+│  gemini-2.5-pro                 12       88.5% (Resets in 4h 30m)   │
+│  gemini-3-pro-preview            2       90.0% (Resets in 20h)      │
+";
+        let now = Local::now();
+        let data = parse_gemini_output(sample, now).unwrap();
+        let ids: Vec<&str> = data.quotas.iter().map(|q| q.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "daily_gemini_2_5_flash",
+                "daily_gemini_2_5_pro",
+                "daily_gemini_3_pro_preview"
+            ]
+        );
+
+        let pro = &data.quotas[1];
+        assert_eq!(pro.label, "Daily (2.5 Pro)");
+        assert_eq!(pro.used_percent, 11.5);
+        assert_eq!(pro.period, PeriodType::Daily);
+        let reset = parse_reset_time(pro.reset.as_deref().unwrap()).unwrap();
+        let expected = now + ChronoDuration::minutes(4 * 60 + 30);
+        assert!((reset - expected).num_seconds().abs() <= RESET_ROUNDING_SECS / 2 + 60);
+
+        assert_eq!(data.quotas[2].label, "Daily (gemini-3-pro-preview)");
+    }
+
+    #[test]
+    fn reset_is_stable_across_refreshes() {
+        let table = |remaining: &str| format!("gemini-2.5-pro 12 88.5% (Resets in {})", remaining);
+        let at = |h: u32, m: u32, s: u32| Local.with_ymd_and_hms(2025, 1, 6, h, m, s).unwrap();
+        let reset = |now: DateTime<Local>, remaining: &str| {
+            let data = parse_gemini_output(&table(remaining), now).unwrap();
+            data.quotas[0].reset.clone().unwrap()
+        };
+
+        // The same reset seen four minutes later, and again with the CLI
+        // rounding the countdown down to the minute
+        assert_eq!(reset(at(10, 2, 0), "4h 30m"), "2025-01-06 14:30");
+        assert_eq!(reset(at(10, 6, 0), "4h 26m"), "2025-01-06 14:30");
+        assert_eq!(reset(at(10, 6, 40), "4h 25m"), "2025-01-06 14:30");
+    }
+
+    #[test]
+    fn reset_a_day_away_stays_tomorrow() {
+        // Rounds up past the current time of day, so the date has to carry it
+        let now = Local.with_ymd_and_hms(2025, 1, 6, 9, 54, 0).unwrap();
+        let data =
+            parse_gemini_output("gemini-2.5-flash - 100.0% (Resets in 23h 59m)", now).unwrap();
+        let reset = data.quotas[0].reset.as_deref().unwrap();
+        assert_eq!(reset, "2025-01-07 10:00");
+        assert_eq!(
+            parse_reset_time(reset),
+            Some(Local.with_ymd_and_hms(2025, 1, 7, 10, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn classifies_screens_without_limits() {
+        let login = "Get started\n\nHow would you like to authenticate for this project?\n";
+        let error = parse_gemini_output(login, Local::now()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<FetchError>(),
+            Some(&FetchError::NotLoggedIn {
+                login_command: "gemini"
+            })
+        );
+
+        let api_key =
+            "Model Usage     Reqs   Input Tokens   Output Tokens\ngemini-2.5-pro  3  1,204  880\n";
+        let error = parse_gemini_output(api_key, Local::now()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<FetchError>(),
+            Some(&FetchError::UnrecognizedOutput)
+        );
+    }
+
+    #[test]
+    fn parses_relative_durations() {
+        assert_eq!(
+            parse_relative("23h 12m"),
+            Some(ChronoDuration::minutes(23 * 60 + 12))
+        );
+        assert_eq!(parse_relative("1d 2h"), Some(ChronoDuration::hours(26)));
+        assert_eq!(parse_relative("soon"), None);
+    }
+}
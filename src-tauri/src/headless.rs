@@ -0,0 +1,137 @@
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::alert::{configured_channels, AlertChannel, FileChannel, LogChannel};
+use crate::history::HistoryStore;
+use crate::notification::{NotificationState, STATE_FILE};
+use crate::settings::load_settings_file;
+use crate::{refresh, refresh_loop, start_api, AppState};
+
+/// Bundle identifier from tauri.conf.json; the app stores its data under it
+const APP_IDENTIFIER: &str = "com.fcoury.notifai";
+
+/// Command-line options for headless mode
+#[derive(Debug, Default, PartialEq)]
+struct HeadlessOptions {
+    /// Directory holding settings.json, history.db and notifications.json
+    /// (defaults to the app's data dir)
+    data_dir: Option<PathBuf>,
+    /// Append alerts as JSON lines to this file
+    alerts_file: Option<PathBuf>,
+}
+
+impl HeadlessOptions {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut opts = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => {}
+                "--data-dir" => {
+                    opts.data_dir = Some(args.next().context("--data-dir needs a path")?.into());
+                }
+                "--alerts-file" => {
+                    opts.alerts_file =
+                        Some(args.next().context("--alerts-file needs a path")?.into());
+                }
+                other => bail!("Unknown argument: {}", other),
+            }
+        }
+        Ok(opts)
+    }
+}
+
+/// Same location Tauri resolves `app_data_dir()` to
+pub(crate) fn default_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+/// App state with settings and history loaded from a data dir, outside of Tauri
+pub(crate) fn open_state(data_dir: &Path) -> Arc<Mutex<AppState>> {
+    let history = match HistoryStore::open(&data_dir.join("history.db")) {
+        Ok(store) => Some(store),
+        Err(e) => {
+            eprintln!("[NotifAI] Failed to open history: {:#}", e);
+            None
+        }
+    };
+
+    let mut state = AppState::new();
+    state.settings = load_settings_file(&data_dir.join("settings.json"));
+    state.history = history;
+    Arc::new(Mutex::new(state))
+}
+
+/// Monitor quotas without a tray or windows (e.g. on servers without a desktop
+/// session). Alerts go to stdout and, optionally, a JSON-lines file. Settings are
+/// read from the app's settings.json and reloaded every cycle.
+pub fn run_headless(args: Vec<String>) -> Result<()> {
+    let opts = HeadlessOptions::parse(args)?;
+    let data_dir = opts
+        .data_dir
+        .or_else(default_data_dir)
+        .context("Could not resolve data dir, pass --data-dir")?;
+    let settings_path = data_dir.join("settings.json");
+    eprintln!(
+        "[NotifAI] Running headless with data dir {}",
+        data_dir.display()
+    );
+
+    let state = open_state(&data_dir);
+    let notif_state = Arc::new(Mutex::new(NotificationState::open(&data_dir.join(STATE_FILE))));
+
+    let alerts_file = opts.alerts_file;
+
+    let tick: Arc<dyn Fn() + Send + Sync> = {
+        let state = state.clone();
+        Arc::new(move || {
+            // Settings may have changed since the last cycle (e.g. new webhooks)
+            let settings = load_settings_file(&settings_path);
+            let mut channels: Vec<Box<dyn AlertChannel>> = vec![Box::new(LogChannel)];
+            if let Some(path) = &alerts_file {
+                channels.push(Box::new(FileChannel::new(path.clone())));
+            }
+            channels.extend(configured_channels(&settings));
+            state.lock().unwrap().settings = settings;
+
+            if let Some(status) = refresh(&state, &notif_state, &channels) {
+                eprintln!("[NotifAI] Refresh complete, worst status: {:?}", status);
+            }
+        })
+    };
+
+    start_api(&state, tick.clone());
+    tick();
+    refresh_loop(state, move || tick());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let opts = HeadlessOptions::parse(args(&[
+            "--headless",
+            "--data-dir",
+            "/var/lib/notifai",
+            "--alerts-file",
+            "/var/log/notifai.jsonl",
+        ]))
+        .unwrap();
+        assert_eq!(opts.data_dir, Some(PathBuf::from("/var/lib/notifai")));
+        assert_eq!(
+            opts.alerts_file,
+            Some(PathBuf::from("/var/log/notifai.jsonl"))
+        );
+
+        assert!(HeadlessOptions::parse(args(&["--data-dir"])).is_err());
+        assert!(HeadlessOptions::parse(args(&["--verbose"])).is_err());
+    }
+}
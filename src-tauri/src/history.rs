@@ -0,0 +1,274 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeZone};
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::projection::parse_reset_time;
+use crate::usage::UsageData;
+
+/// A single recorded quota reading
+#[derive(Debug, Clone, Serialize)]
+pub struct Sample {
+    pub provider: String,
+    pub quota: String,
+    pub timestamp: DateTime<Local>,
+    pub used_percent: f32,
+    /// Parsed reset instant at the time of the reading
+    pub reset_at: Option<DateTime<Local>>,
+    /// Length of the quota period in seconds
+    pub period_secs: i64,
+}
+
+/// Final reading of a quota period that has rolled over
+#[derive(Debug, Clone, Serialize)]
+pub struct ClosedPeriod {
+    pub provider: String,
+    pub quota: String,
+    pub ended_at: DateTime<Local>,
+    pub final_percent: f32,
+}
+
+/// SQLite-backed store of every successful fetch, for time-series queries
+pub struct HistoryStore {
+    conn: Connection,
+}
+
+impl HistoryStore {
+    /// Open (or create) the history database at the given path
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).context("Failed to create history directory")?;
+        }
+        let conn = Connection::open(path).context("Failed to open history database")?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS samples (
+                 provider     TEXT    NOT NULL,
+                 quota        TEXT    NOT NULL,
+                 timestamp    INTEGER NOT NULL,
+                 used_percent REAL    NOT NULL,
+                 reset_at     INTEGER,
+                 period_secs  INTEGER NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS samples_quota_time
+                 ON samples (provider, quota, timestamp);
+             CREATE INDEX IF NOT EXISTS samples_time ON samples (timestamp);
+             CREATE TABLE IF NOT EXISTS periods (
+                 provider      TEXT    NOT NULL,
+                 quota         TEXT    NOT NULL,
+                 ended_at      INTEGER NOT NULL,
+                 final_percent REAL    NOT NULL,
+                 PRIMARY KEY (provider, quota, ended_at)
+             );",
+        )
+        .context("Failed to initialize history schema")?;
+        Ok(Self { conn })
+    }
+
+    /// Record every quota of a fetch result
+    pub fn record(&self, provider: &str, usage: &UsageData, at: DateTime<Local>) -> Result<()> {
+        for quota in &usage.quotas {
+            let reset_at = quota.reset.as_deref().and_then(parse_reset_time);
+            self.conn.execute(
+                "INSERT INTO samples (provider, quota, timestamp, used_percent, reset_at, period_secs)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    provider,
+                    quota.id,
+                    at.timestamp(),
+                    quota.used_percent,
+                    reset_at.map(|r| r.timestamp()),
+                    quota.period.duration().num_seconds(),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Samples of a quota in its current period, oldest first.
+    ///
+    /// The current period is derived from the latest sample: it started one period
+    /// length before that sample's reset time.
+    pub fn current_period_samples(&self, provider: &str, quota: &str) -> Result<Vec<Sample>> {
+        let latest: Option<(Option<i64>, i64)> = self
+            .conn
+            .query_row(
+                "SELECT reset_at, period_secs FROM samples
+                 WHERE provider = ?1 AND quota = ?2
+                 ORDER BY timestamp DESC LIMIT 1",
+                params![provider, quota],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok();
+
+        let Some((Some(reset_at), period_secs)) = latest else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT provider, quota, timestamp, used_percent, reset_at, period_secs FROM samples
+             WHERE provider = ?1 AND quota = ?2 AND timestamp >= ?3 AND timestamp <= ?4
+             ORDER BY timestamp",
+        )?;
+        let samples = stmt
+            .query_map(
+                params![provider, quota, reset_at - period_secs, reset_at],
+                sample_from_row,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(samples)
+    }
+
+    /// Record the final percent of a period that just reset
+    pub fn record_period_end(
+        &self,
+        provider: &str,
+        quota: &str,
+        ended_at: DateTime<Local>,
+        final_percent: f32,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO periods (provider, quota, ended_at, final_percent)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![provider, quota, ended_at.timestamp(), final_percent],
+        )?;
+        Ok(())
+    }
+
+    /// Closed periods of a quota, most recent first
+    pub fn closed_periods(
+        &self,
+        provider: &str,
+        quota: &str,
+        limit: u32,
+    ) -> Result<Vec<ClosedPeriod>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT provider, quota, ended_at, final_percent FROM periods
+             WHERE provider = ?1 AND quota = ?2
+             ORDER BY ended_at DESC LIMIT ?3",
+        )?;
+        let periods = stmt
+            .query_map(params![provider, quota, limit], |row| {
+                Ok(ClosedPeriod {
+                    provider: row.get(0)?,
+                    quota: row.get(1)?,
+                    ended_at: Local
+                        .timestamp_opt(row.get(2)?, 0)
+                        .single()
+                        .unwrap_or_else(Local::now),
+                    final_percent: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(periods)
+    }
+
+    /// All samples (every provider and quota) recorded between `from` and `to`, oldest first
+    pub fn samples_between(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+    ) -> Result<Vec<Sample>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT provider, quota, timestamp, used_percent, reset_at, period_secs FROM samples
+             WHERE timestamp >= ?1 AND timestamp <= ?2
+             ORDER BY timestamp",
+        )?;
+        let samples = stmt
+            .query_map(params![from.timestamp(), to.timestamp()], sample_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(samples)
+    }
+}
+
+fn sample_from_row(row: &Row) -> rusqlite::Result<Sample> {
+    let to_local = |secs: i64| Local.timestamp_opt(secs, 0).single();
+    Ok(Sample {
+        provider: row.get(0)?,
+        quota: row.get(1)?,
+        timestamp: to_local(row.get(2)?).unwrap_or_else(Local::now),
+        used_percent: row.get(3)?,
+        reset_at: row.get::<_, Option<i64>>(4)?.and_then(to_local),
+        period_secs: row.get(5)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projection::PeriodType;
+    use crate::usage::Quota;
+    use chrono::Duration;
+
+    fn usage_with(percent: f32, reset: &str) -> UsageData {
+        let mut usage = UsageData::new();
+        usage.quotas.push(Quota {
+            id: "five_hour".to_string(),
+            label: "5h limit".to_string(),
+            used_percent: percent,
+            reset: Some(reset.to_string()),
+            period: PeriodType::Session,
+        });
+        usage
+    }
+
+    #[test]
+    fn test_record_and_query_between() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let now = Local::now();
+        let reset = (now + Duration::hours(2)).format("%H:%M").to_string();
+
+        store.record("codex", &usage_with(10.0, &reset), now - Duration::hours(1)).unwrap();
+        store.record("codex", &usage_with(20.0, &reset), now).unwrap();
+
+        let all = store.samples_between(now - Duration::hours(2), now).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].used_percent, 10.0);
+        assert_eq!(all[1].quota, "five_hour");
+        assert!(all[1].reset_at.is_some());
+
+        let recent = store.samples_between(now - Duration::minutes(30), now).unwrap();
+        assert_eq!(recent.len(), 1);
+    }
+
+    #[test]
+    fn test_current_period_excludes_previous_period() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let now = Local::now();
+        let reset = (now + Duration::hours(2)).format("%H:%M").to_string();
+
+        // Six hours ago belongs to the previous 5h window
+        store.record("codex", &usage_with(90.0, &reset), now - Duration::hours(6)).unwrap();
+        store.record("codex", &usage_with(5.0, &reset), now - Duration::hours(1)).unwrap();
+        store.record("codex", &usage_with(12.0, &reset), now).unwrap();
+
+        let samples = store.current_period_samples("codex", "five_hour").unwrap();
+        let percents: Vec<f32> = samples.iter().map(|s| s.used_percent).collect();
+        assert_eq!(percents, vec![5.0, 12.0]);
+        assert!(store.current_period_samples("claude", "session").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_closed_periods() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let now = Local::now();
+        store.record_period_end("claude", "session", now - Duration::hours(5), 64.0).unwrap();
+        store.record_period_end("claude", "session", now, 97.0).unwrap();
+        // The same reset seen twice keeps one row
+        store.record_period_end("claude", "session", now, 98.0).unwrap();
+
+        let periods = store.closed_periods("claude", "session", 10).unwrap();
+        let finals: Vec<f32> = periods.iter().map(|p| p.final_percent).collect();
+        assert_eq!(finals, vec![98.0, 64.0]);
+        assert!(store.closed_periods("codex", "five_hour", 10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::alert::AlertChannel;
+use crate::events::QuotaEvent;
+use crate::notification::NotificationInfo;
+
+/// Hooks still running after this long are killed
+const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Shell commands run on quota events; empty strings disable a hook
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HookCommands {
+    pub on_notification: String,
+    pub on_reset: String,
+    pub on_status_change: String,
+}
+
+impl HookCommands {
+    /// Command configured for an event, if any
+    pub fn command_for(&self, event: &QuotaEvent) -> Option<&str> {
+        let command = match event {
+            QuotaEvent::Notification { .. } => &self.on_notification,
+            QuotaEvent::Reset { .. } => &self.on_reset,
+            QuotaEvent::StatusChange { .. } => &self.on_status_change,
+        };
+        Some(command.trim()).filter(|c| !c.is_empty())
+    }
+}
+
+/// Run the hooks configured for each event
+pub fn dispatch_events(hooks: &HookCommands, events: &[QuotaEvent]) {
+    for event in events {
+        if let Some(command) = hooks.command_for(event) {
+            spawn_hook(command, event);
+        }
+    }
+}
+
+/// Runs the notification hook for every alert
+pub struct HookChannel {
+    command: String,
+}
+
+impl HookChannel {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+        }
+    }
+}
+
+impl AlertChannel for HookChannel {
+    fn name(&self) -> &str {
+        "hook"
+    }
+
+    fn send(&self, info: &NotificationInfo) -> Result<()> {
+        spawn_hook(&self.command, &QuotaEvent::from(info));
+        Ok(())
+    }
+}
+
+/// Environment passed to hooks: every top-level field of the event JSON as
+/// `NOTIFAI_<FIELD>`, e.g. `NOTIFAI_EVENT=reset`, `NOTIFAI_QUOTA=session`
+pub fn hook_env(payload: &Value) -> Vec<(String, String)> {
+    let Some(fields) = payload.as_object() else {
+        return Vec::new();
+    };
+    fields
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            (format!("NOTIFAI_{}", key.to_uppercase()), value)
+        })
+        .collect()
+}
+
+/// Run a hook in the background, logging failures
+fn spawn_hook(command: &str, event: &QuotaEvent) {
+    let command = command.to_string();
+    let payload = serde_json::to_value(event).unwrap_or(Value::Null);
+    let kind = event.kind();
+    thread::spawn(move || {
+        if let Err(e) = run_hook(&command, &payload) {
+            eprintln!("[NotifAI] {} hook failed: {:#}", kind, e);
+        }
+    });
+}
+
+/// Run a hook through the shell with the event in its environment and as JSON on stdin
+pub fn run_hook(command: &str, payload: &Value) -> Result<()> {
+    let mut child = shell(command)
+        .envs(hook_env(payload))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to run `{}`", command))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores stdin may exit before reading it; that's fine
+        let _ = writeln!(stdin, "{}", payload);
+    }
+
+    let status = wait_with_timeout(&mut child, HOOK_TIMEOUT)?;
+    if !status.success() {
+        bail!("`{}` exited with {}", command, status);
+    }
+    Ok(())
+}
+
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<std::process::ExitStatus> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if start.elapsed() > timeout {
+            let _ = child.kill();
+            bail!("timed out after {}s", timeout.as_secs());
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projection::BudgetStatus;
+
+    fn status_change() -> QuotaEvent {
+        QuotaEvent::StatusChange {
+            provider: "claude".to_string(),
+            quota: "week_all_models".to_string(),
+            label: "Week (all models)".to_string(),
+            previous_status: BudgetStatus::OnTrack,
+            status: BudgetStatus::OverBudget,
+            projected_percent: 130.0,
+        }
+    }
+
+    #[test]
+    fn test_hook_env() {
+        let payload = serde_json::to_value(status_change()).unwrap();
+        let env = hook_env(&payload);
+        let get = |key: &str| env.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(get("NOTIFAI_EVENT"), Some("status_change"));
+        assert_eq!(get("NOTIFAI_QUOTA"), Some("week_all_models"));
+        assert_eq!(get("NOTIFAI_STATUS"), Some("OverBudget"));
+        assert_eq!(get("NOTIFAI_PROJECTED_PERCENT"), Some("130.0"));
+    }
+
+    #[test]
+    fn test_command_for_event() {
+        let hooks = HookCommands {
+            on_status_change: "  pause-agents  ".to_string(),
+            ..HookCommands::default()
+        };
+        assert_eq!(hooks.command_for(&status_change()), Some("pause-agents"));
+        assert_eq!(HookCommands::default().command_for(&status_change()), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_hook_passes_env_and_stdin() {
+        let out = std::env::temp_dir().join(format!("notifai-hook-{}.txt", std::process::id()));
+        let command = format!(
+            "read -r json; echo \"$NOTIFAI_EVENT $NOTIFAI_STATUS $json\" > {}",
+            out.display()
+        );
+        let payload = serde_json::to_value(status_change()).unwrap();
+        run_hook(&command, &payload).unwrap();
+
+        let written = std::fs::read_to_string(&out).unwrap();
+        assert!(written.starts_with("status_change OverBudget {"));
+        assert!(written.contains("\"previous_status\":\"OnTrack\""));
+        std::fs::remove_file(&out).unwrap();
+
+        assert!(run_hook("exit 3", &payload).is_err());
+    }
+}
@@ -1,13 +1,41 @@
 mod notification;
+mod alert;
+mod api;
+mod attribution;
+mod cli;
 mod codex;
+mod events;
+mod gemini;
+mod headless;
+mod history;
+mod hooks;
+mod metrics;
 mod projection;
+mod provider;
+mod quiet;
+mod rules;
+mod schedule;
 mod settings;
+mod terminal;
+mod tokens;
+mod transcripts;
 mod usage;
+mod webhook;
 
+use alert::AlertChannel;
+use attribution::QuotaAttribution;
 use chrono::{DateTime, Local};
-use notification::{check_notifications, NotificationState};
-use projection::{calculate_all_projections, format_duration_secs, BudgetStatus, QuotaProjection};
+use events::QuotaEvent;
+use history::{HistoryStore, Sample};
+use notification::{NotificationInfo, NotificationState};
+use projection::{
+    calculate_all_projections, format_clock_time, format_duration_secs, BudgetStatus,
+    ProjectionMethod, QuotaProjection,
+};
+use provider::FetchError;
+use quiet::QuietHours;
 use settings::{load_settings, save_settings, Settings};
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -19,148 +47,202 @@ use tauri::{
     AppHandle, Manager, WebviewUrl, WebviewWindowBuilder,
 };
 use tauri_plugin_notification::NotificationExt;
+use transcripts::TranscriptScanner;
+
+pub use cli::run_status;
+pub use headless::run_headless;
+
+/// Latest fetch results for a single provider
+struct ProviderState {
+    usage: usage::UsageData,
+    projection: QuotaProjection,
+}
+
+/// Fetch outcome counters for a single provider
+#[derive(Debug, Default)]
+struct FetchStats {
+    last_success: Option<DateTime<Local>>,
+    failures_total: u64,
+    /// Failed fetches since the last success
+    consecutive_failures: u32,
+    /// Error of the last fetch, None if it succeeded
+    last_error: Option<String>,
+    /// Typed cause of `last_error`, when the provider knows it
+    last_cause: Option<FetchError>,
+}
+
+impl FetchStats {
+    /// Menu rows for a provider whose last fetch failed: the error and how old
+    /// the data shown is
+    fn stale_lines(&self, now: DateTime<Local>) -> Vec<String> {
+        let Some(error) = &self.last_error else {
+            return Vec::new();
+        };
+        let mut error = error.lines().next().unwrap_or_default().to_string();
+        if error.chars().count() > STALE_ERROR_MAX_CHARS {
+            error = error.chars().take(STALE_ERROR_MAX_CHARS - 1).collect::<String>() + "…";
+        }
+        let age = match self.last_success {
+            Some(at) => format!("Showing data from {}", format_ago(now - at)),
+            None => "No data yet".to_string(),
+        };
+        let mut lines = vec![format!("⚠ Fetch failed: {}", error)];
+        lines.extend(self.last_cause.as_ref().map(FetchError::hint));
+        lines.push(age);
+        lines
+    }
+}
+
+/// Longer errors are cut off in the menu
+const STALE_ERROR_MAX_CHARS: usize = 60;
 
 /// Application state
 struct AppState {
-    usage_claude: Option<usage::UsageData>,
-    usage_codex: Option<usage::UsageData>,
-    projection_claude: Option<QuotaProjection>,
-    projection_codex: Option<QuotaProjection>,
+    /// Keyed by provider id; results of the last successful fetch, kept while
+    /// later fetches fail (see `fetch_stats`)
+    providers: HashMap<String, ProviderState>,
+    /// Keyed by provider id, kept across refreshes
+    fetch_stats: HashMap<String, FetchStats>,
     last_refresh: Option<DateTime<Local>>,
     is_refreshing: AtomicBool,
     settings: Settings,
+    /// Usage history (None if the database couldn't be opened)
+    history: Option<HistoryStore>,
+    /// Keyed by provider id; token counts read from the CLI's own transcripts
+    transcripts: HashMap<String, TranscriptScanner>,
 }
 
 impl AppState {
     fn new() -> Self {
         Self {
-            usage_claude: None,
-            usage_codex: None,
-            projection_claude: None,
-            projection_codex: None,
+            providers: HashMap::new(),
+            fetch_stats: HashMap::new(),
             last_refresh: None,
             is_refreshing: AtomicBool::new(false),
             settings: Settings::default(),
+            history: None,
+            transcripts: HashMap::new(),
         }
     }
+
+    /// Whether the last fetch of any enabled provider failed after earlier ones
+    /// succeeded, so the data shown is outdated. Providers that never fetched
+    /// (e.g. a CLI that isn't installed) show no data and don't count.
+    fn is_stale(&self) -> bool {
+        provider::registry(&self.settings).iter().any(|p| {
+            self.fetch_stats
+                .get(p.id())
+                .is_some_and(|s| s.last_error.is_some() && s.last_success.is_some())
+        })
+    }
 }
 
-fn build_usage_menu(app: &AppHandle, state: &AppState) -> Menu<tauri::Wry> {
+/// "just now" or e.g. "5m ago"
+fn format_ago(elapsed: chrono::Duration) -> String {
+    if elapsed.num_seconds() < 60 {
+        "just now".to_string()
+    } else {
+        format!("{} ago", format_duration_secs(elapsed.num_seconds()))
+    }
+}
+
+fn build_usage_menu(
+    app: &AppHandle,
+    state: &AppState,
+    snoozed_until: Option<DateTime<Local>>,
+) -> Menu<tauri::Wry> {
     let menu = Menu::new(app).unwrap();
 
-    // Claude section
-    let claude_header =
-        MenuItem::with_id(app, "hdr_claude", "Claude", false, None::<&str>).unwrap();
-    let _ = menu.append(&claude_header);
-    if let (Some(usage), Some(proj)) = (&state.usage_claude, &state.projection_claude) {
-        if let Some(session) = &proj.session {
-            let _ = menu.append(&MenuItem::with_id(
-                app,
-                "session",
-                format!(
-                    "{} Session: {}% → {}% (resets in {})",
-                    session.status.indicator(),
-                    session.current_percent as i32,
-                    session.projected_percent as i32,
-                    session.format_time_remaining()
-                ),
-                false,
-                None::<&str>,
-            )
-            .unwrap());
-        }
-        if let Some(week_all) = &proj.week_all {
-            let _ = menu.append(&MenuItem::with_id(
-                app,
-                "week_all",
-                format!(
-                    "{} Week (all): {}% → {}% (resets in {})",
-                    week_all.status.indicator(),
-                    week_all.current_percent as i32,
-                    week_all.projected_percent as i32,
-                    week_all.format_time_remaining()
-                ),
-                false,
-                None::<&str>,
-            )
-            .unwrap());
-        }
-        if let Some(week_sonnet) = &proj.week_sonnet {
-            let _ = menu.append(&MenuItem::with_id(
-                app,
-                "week_sonnet",
-                format!(
-                    "{} Week (Sonnet): {}% → {}% (resets in {})",
-                    week_sonnet.status.indicator(),
-                    week_sonnet.current_percent as i32,
-                    week_sonnet.projected_percent as i32,
-                    week_sonnet.format_time_remaining()
-                ),
-                false,
-                None::<&str>,
-            )
-            .unwrap());
+    // One section per registered provider
+    for (i, provider) in provider::registry(&state.settings).iter().enumerate() {
+        if i > 0 {
+            let _ = menu.append(&PredefinedMenuItem::separator(app).unwrap());
         }
-        let extra_text = if usage.extra_usage_enabled {
-            "enabled"
-        } else {
-            "not enabled"
-        };
-        let _ = menu.append(
-            &MenuItem::with_id(app, "extra", format!("Extra usage: {}", extra_text), false, None::<&str>)
-                .unwrap(),
-        );
-    } else {
-        let _ = menu.append(
-            &MenuItem::with_id(app, "claude_loading", "Loading Claude usage...", false, None::<&str>)
-                .unwrap(),
-        );
-    }
 
-    let _ = menu.append(&PredefinedMenuItem::separator(app).unwrap());
+        let header = MenuItem::with_id(
+            app,
+            format!("hdr_{}", provider.id()),
+            provider.display_name(),
+            false,
+            None::<&str>,
+        )
+        .unwrap();
+        let _ = menu.append(&header);
 
-    // Codex section
-    let codex_header =
-        MenuItem::with_id(app, "hdr_codex", "Codex", false, None::<&str>).unwrap();
-    let _ = menu.append(&codex_header);
-    if let Some(proj) = &state.projection_codex {
-        if let Some(codex5h) = &proj.codex_five_hour {
-            let _ = menu.append(&MenuItem::with_id(
-                app,
-                "codex_5h",
-                format!(
-                    "{} 5h limit: {}% → {}% (resets in {})",
-                    codex5h.status.indicator(),
-                    codex5h.current_percent as i32,
-                    codex5h.projected_percent as i32,
-                    codex5h.format_time_remaining()
-                ),
-                false,
-                None::<&str>,
-            )
-            .unwrap());
+        let stats = state.fetch_stats.get(provider.id());
+        let stale_lines = stats.map_or_else(Vec::new, |s| s.stale_lines(Local::now()));
+        for (j, line) in stale_lines.iter().enumerate() {
+            let _ = menu.append(
+                &MenuItem::with_id(
+                    app,
+                    format!("{}_stale_{}", provider.id(), j),
+                    line,
+                    false,
+                    None::<&str>,
+                )
+                .unwrap(),
+            );
         }
-        if let Some(codex_week) = &proj.codex_week {
-            let _ = menu.append(&MenuItem::with_id(
-                app,
-                "codex_week",
-                format!(
-                    "{} Weekly limit: {}% → {}% (resets in {})",
-                    codex_week.status.indicator(),
-                    codex_week.current_percent as i32,
-                    codex_week.projected_percent as i32,
-                    codex_week.format_time_remaining()
-                ),
-                false,
-                None::<&str>,
-            )
-            .unwrap());
+
+        if let Some(provider_state) = state.providers.get(provider.id()) {
+            for quota in &provider_state.projection.quotas {
+                let _ = menu.append(&MenuItem::with_id(
+                    app,
+                    format!("{}_{}", provider.id(), quota.id),
+                    quota.summary(),
+                    false,
+                    None::<&str>,
+                )
+                .unwrap());
+            }
+            for (j, line) in provider.extra_lines(&provider_state.usage).iter().enumerate() {
+                let _ = menu.append(
+                    &MenuItem::with_id(
+                        app,
+                        format!("{}_extra_{}", provider.id(), j),
+                        line,
+                        false,
+                        None::<&str>,
+                    )
+                    .unwrap(),
+                );
+            }
+        } else if stale_lines.is_empty() {
+            let _ = menu.append(
+                &MenuItem::with_id(
+                    app,
+                    format!("{}_loading", provider.id()),
+                    format!("Loading {} usage...", provider.display_name()),
+                    false,
+                    None::<&str>,
+                )
+                .unwrap(),
+            );
         }
-    } else {
-        let _ = menu.append(
-            &MenuItem::with_id(app, "codex_loading", "Loading Codex usage...", false, None::<&str>)
+
+        // Today's tokens from the transcripts, then the projects that used them
+        let token_lines = state.transcripts.get(provider.id()).map_or_else(Vec::new, |s| {
+            let now = Local::now();
+            [
+                s.ledger.today_line(now, &state.settings.pricing),
+                s.ledger.projects_line(now),
+            ]
+            .into_iter()
+            .flatten()
+            .collect()
+        });
+        for (j, line) in token_lines.iter().enumerate() {
+            let _ = menu.append(
+                &MenuItem::with_id(
+                    app,
+                    format!("{}_tokens_{}", provider.id(), j),
+                    line,
+                    false,
+                    None::<&str>,
+                )
                 .unwrap(),
-        );
+            );
+        }
     }
 
     // Separator and actions
@@ -169,15 +251,10 @@ fn build_usage_menu(app: &AppHandle, state: &AppState) -> Menu<tauri::Wry> {
     // Show last updated time
     if let Some(last_refresh) = &state.last_refresh {
         let elapsed = Local::now().signed_duration_since(*last_refresh);
-        let ago_text = if elapsed.num_seconds() < 60 {
-            "just now".to_string()
-        } else {
-            format!("{} ago", format_duration_secs(elapsed.num_seconds()))
-        };
         let last_updated = MenuItem::with_id(
             app,
             "last_updated",
-            format!("Updated {}", ago_text),
+            format!("Updated {}", format_ago(elapsed)),
             false,
             None::<&str>,
         )
@@ -186,6 +263,39 @@ fn build_usage_menu(app: &AppHandle, state: &AppState) -> Menu<tauri::Wry> {
         let _ = menu.append(&PredefinedMenuItem::separator(app).unwrap());
     }
 
+    // Snooze actions
+    if let Some(until) = snoozed_until {
+        let _ = menu.append(
+            &MenuItem::with_id(
+                app,
+                "snoozed",
+                format!("Alerts snoozed until {}", format_clock_time(until)),
+                false,
+                None::<&str>,
+            )
+            .unwrap(),
+        );
+        let _ = menu.append(
+            &MenuItem::with_id(app, "snooze_off", "Resume Alerts", true, None::<&str>).unwrap(),
+        );
+    } else {
+        let _ = menu.append(
+            &MenuItem::with_id(app, "snooze_1h", "Snooze Alerts for 1 Hour", true, None::<&str>)
+                .unwrap(),
+        );
+        let _ = menu.append(
+            &MenuItem::with_id(
+                app,
+                "snooze_reset",
+                "Snooze Alerts Until Reset",
+                next_reset(state).is_some(),
+                None::<&str>,
+            )
+            .unwrap(),
+        );
+    }
+    let _ = menu.append(&PredefinedMenuItem::separator(app).unwrap());
+
     // Settings item with Cmd+, accelerator
     let settings_item = MenuItem::with_id(
         app,
@@ -197,6 +307,10 @@ fn build_usage_menu(app: &AppHandle, state: &AppState) -> Menu<tauri::Wry> {
     .unwrap();
     let _ = menu.append(&settings_item);
 
+    let dashboard =
+        MenuItem::with_id(app, "dashboard", "Project Usage...", true, None::<&str>).unwrap();
+    let _ = menu.append(&dashboard);
+
     // About item
     let about = MenuItem::with_id(app, "about", "About NotifAI", true, None::<&str>).unwrap();
     let _ = menu.append(&about);
@@ -212,6 +326,38 @@ fn build_usage_menu(app: &AppHandle, state: &AppState) -> Menu<tauri::Wry> {
     menu
 }
 
+/// Soonest upcoming reset across all quotas
+fn next_reset(state: &AppState) -> Option<DateTime<Local>> {
+    let now = Local::now();
+    state
+        .providers
+        .values()
+        .flat_map(|p| &p.projection.quotas)
+        .map(|q| q.projected.reset_at)
+        .filter(|at| *at > now)
+        .min()
+}
+
+/// Rebuild the tray menu from the current state
+fn update_menu(
+    app: &AppHandle,
+    state: &Arc<Mutex<AppState>>,
+    notif_state: &Arc<Mutex<NotificationState>>,
+) {
+    let snoozed_until = notif_state.lock().unwrap().snoozed_until(Local::now());
+    let state_guard = state.lock().unwrap();
+    eprintln!(
+        "[NotifAI] Building menu with state: providers={:?}",
+        state_guard.providers.keys().collect::<Vec<_>>()
+    );
+    let menu = build_usage_menu(app, &state_guard, snoozed_until);
+    if let Some(tray) = app.tray_by_id("main") {
+        let _ = tray.set_menu(Some(menu));
+    } else {
+        eprintln!("[NotifAI] ERROR: Could not find tray with id 'main'");
+    }
+}
+
 /// Get the appropriate icon for the given status
 fn get_status_icon(status: BudgetStatus) -> Image<'static> {
     match status {
@@ -222,9 +368,14 @@ fn get_status_icon(status: BudgetStatus) -> Image<'static> {
     }
 }
 
-/// Update the tray icon based on the worst status
-fn update_tray_icon(tray: &TrayIcon, status: BudgetStatus) {
-    let icon = get_status_icon(status);
+/// Update the tray icon based on the worst status, or show the stale icon when
+/// the numbers can't be trusted
+fn update_tray_icon(tray: &TrayIcon, status: BudgetStatus, stale: bool) {
+    let icon = if stale {
+        tauri::include_image!("icons/tray-stale.png")
+    } else {
+        get_status_icon(status)
+    };
     let _ = tray.set_icon(Some(icon));
 }
 
@@ -240,12 +391,30 @@ fn open_settings_window(app: &AppHandle) {
     // Create new window
     let _ = WebviewWindowBuilder::new(app, "settings", WebviewUrl::App("settings.html".into()))
         .title("NotifAI Settings")
-        .inner_size(480.0, 420.0)
+        .inner_size(480.0, 640.0)
         .resizable(false)
         .center()
         .build();
 }
 
+/// Open the dashboard window with the per-project breakdown of weekly quotas
+fn open_dashboard_window(app: &AppHandle) {
+    // Check if window already exists
+    if let Some(window) = app.get_webview_window("dashboard") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+
+    // Create new window
+    let _ = WebviewWindowBuilder::new(app, "dashboard", WebviewUrl::App("dashboard.html".into()))
+        .title("NotifAI Project Usage")
+        .inner_size(480.0, 520.0)
+        .resizable(true)
+        .center()
+        .build();
+}
+
 /// Open the about window
 fn open_about_window(app: &AppHandle) {
     // Check if window already exists
@@ -264,76 +433,181 @@ fn open_about_window(app: &AppHandle) {
         .build();
 }
 
-/// Fetch usage and update state
-fn fetch_and_update(
-    app: &AppHandle,
+/// Record a fetch result in history and return the current-period samples per
+/// quota id (empty when burn-rate projections are off or history is unavailable)
+fn record_history(
     state: &Arc<Mutex<AppState>>,
-    notif_state: &Arc<Mutex<NotificationState>>,
-) {
-    eprintln!("[NotifAI] fetch_and_update called");
+    provider_id: &str,
+    usage: &usage::UsageData,
+    method: ProjectionMethod,
+) -> HashMap<String, Vec<Sample>> {
+    let guard = state.lock().unwrap();
+    let Some(history) = &guard.history else {
+        return HashMap::new();
+    };
+
+    if let Err(e) = history.record(provider_id, usage, Local::now()) {
+        eprintln!("[NotifAI] Failed to record {} history: {}", provider_id, e);
+    }
+
+    if method != ProjectionMethod::BurnRate {
+        return HashMap::new();
+    }
+    usage
+        .quotas
+        .iter()
+        .filter_map(|q| match history.current_period_samples(provider_id, &q.id) {
+            Ok(samples) => Some((q.id.clone(), samples)),
+            Err(e) => {
+                eprintln!("[NotifAI] Failed to load {} history: {}", q.id, e);
+                None
+            }
+        })
+        .collect()
+}
 
+/// Desktop notifications through the Tauri notification plugin
+struct DesktopChannel {
+    app: AppHandle,
+}
+
+impl AlertChannel for DesktopChannel {
+    fn name(&self) -> &str {
+        "desktop"
+    }
+
+    fn send(&self, info: &notification::NotificationInfo) -> anyhow::Result<()> {
+        self.app
+            .notification()
+            .builder()
+            .title(info.title())
+            .body(info.body())
+            .show()?;
+        Ok(())
+    }
+}
+
+/// Fetch every provider, compute projections, send alerts and update state.
+/// Returns the worst status across providers, or None if nothing was fetched.
+fn refresh(
+    state: &Arc<Mutex<AppState>>,
+    notif_state: &Arc<Mutex<NotificationState>>,
+    channels: &[Box<dyn AlertChannel>],
+) -> Option<BudgetStatus> {
     // Get current settings for notification checks
     let current_settings = {
         let guard = state.lock().unwrap();
         guard.settings.clone()
     };
 
-    // Fetch Claude and Codex usage (independent)
-    let claude_usage = usage::fetch_usage();
-    let codex_usage = codex::fetch_codex_usage(&current_settings.codex_path);
-
-    let mut projection_claude: Option<QuotaProjection> = None;
-    let mut projection_codex: Option<QuotaProjection> = None;
-
-    if let Ok(ref u) = claude_usage {
-        eprintln!("[NotifAI] Claude fetch succeeded");
-        projection_claude = Some(calculate_all_projections(
-            u,
-            current_settings.threshold_under_budget,
-            current_settings.threshold_on_track,
-        ));
-    } else if let Err(e) = &claude_usage {
-        eprintln!("[NotifAI] Claude fetch failed: {}", e);
-    }
+    let providers = provider::registry(&current_settings);
 
-    if let Ok(ref u) = codex_usage {
-        eprintln!("[NotifAI] Codex fetch succeeded");
-        projection_codex = Some(calculate_all_projections(
-            u,
-            current_settings.threshold_under_budget,
-            current_settings.threshold_on_track,
-        ));
-    } else if let Err(e) = &codex_usage {
-        eprintln!("[NotifAI] Codex fetch failed: {}", e);
+    // Count tokens logged since the last refresh on copies of the scanners,
+    // outside the lock, so the menu and API keep showing the last counts until
+    // they're swapped in. A provider whose transcripts moved (e.g. a profile's
+    // config dir changed) starts over.
+    let mut scanners = HashMap::new();
+    for provider in &providers {
+        let Some(fresh) = provider.transcripts() else {
+            continue;
+        };
+        let existing = state
+            .lock()
+            .unwrap()
+            .transcripts
+            .get(provider.id())
+            .filter(|existing| existing.root() == fresh.root())
+            .cloned();
+        let mut scanner = existing.unwrap_or(fresh);
+        if let Err(e) = scanner.scan() {
+            eprintln!("[NotifAI] {} transcript scan failed: {:#}", provider.id(), e);
+        }
+        scanners.insert(provider.id().to_string(), scanner);
     }
+    state.lock().unwrap().transcripts = scanners;
 
-    if projection_claude.is_none() && projection_codex.is_none() {
-        eprintln!("[NotifAI] No usage data fetched from any provider");
-        return;
+    // Fetch each provider independently
+    let mut fetched: HashMap<String, ProviderState> = HashMap::new();
+    for provider in &providers {
+        match provider.fetch() {
+            Ok(u) => {
+                eprintln!("[NotifAI] {} fetch succeeded", provider.display_name());
+                {
+                    let mut guard = state.lock().unwrap();
+                    let stats = guard.fetch_stats.entry(provider.id().to_string()).or_default();
+                    stats.last_success = Some(Local::now());
+                    stats.consecutive_failures = 0;
+                    stats.last_error = None;
+                    stats.last_cause = None;
+                }
+                let readings: Vec<_> = u
+                    .quotas
+                    .iter()
+                    .map(|q| (&q.id, q.used_percent, &q.reset))
+                    .collect();
+                eprintln!(
+                    "[NotifAI] {} usage parsed: {:?}",
+                    provider.display_name(),
+                    readings
+                );
+                let samples = record_history(
+                    state,
+                    provider.id(),
+                    &u,
+                    current_settings.projection_method,
+                );
+                let projection = calculate_all_projections(
+                    provider.as_ref(),
+                    &u,
+                    &samples,
+                    &current_settings.work_schedule,
+                    current_settings.threshold_under_budget,
+                    current_settings.threshold_on_track,
+                );
+                fetched.insert(
+                    provider.id().to_string(),
+                    ProviderState {
+                        usage: u,
+                        projection,
+                    },
+                );
+            }
+            Err(e) => {
+                eprintln!("[NotifAI] {} fetch failed: {}", provider.display_name(), e);
+                let failures = {
+                    let mut guard = state.lock().unwrap();
+                    let stats = guard.fetch_stats.entry(provider.id().to_string()).or_default();
+                    stats.failures_total += 1;
+                    stats.consecutive_failures += 1;
+                    stats.last_error = Some(e.to_string());
+                    stats.last_cause = e.downcast_ref::<FetchError>().cloned();
+                    stats.consecutive_failures
+                };
+                // Once per failure streak; a setting of 0 never matches
+                if current_settings.notifications_enabled
+                    && failures == current_settings.failure_alert_after
+                {
+                    let info = NotificationInfo::fetch_failed(
+                        provider.id(),
+                        provider.display_name(),
+                        failures,
+                        &e.to_string(),
+                    );
+                    let mut notif_guard = notif_state.lock().unwrap();
+                    deliver(channels, &mut notif_guard, &current_settings.quiet_hours, info);
+                }
+            }
+        }
     }
 
-    if let Ok(u) = &claude_usage {
-        eprintln!(
-            "[NotifAI] Claude usage parsed: session={:?}, week_all={:?}, week_sonnet={:?}",
-            u.current_session_percent, u.current_week_all_models_percent, u.current_week_sonnet_percent
-        );
-    }
-    if let Ok(u) = &codex_usage {
-        eprintln!(
-            "[NotifAI] Codex usage parsed: five_hour_left={:?}, week_left={:?}",
-            u.codex_five_hour_left, u.codex_week_left
-        );
+    if fetched.is_empty() {
+        eprintln!("[NotifAI] No usage data fetched from any provider");
+        return None;
     }
 
     // Overall worst status for tray icon
     let mut overall_status = BudgetStatus::Unknown;
-    for status in [
-        projection_claude.as_ref().map(|p| p.worst_status()),
-        projection_codex.as_ref().map(|p| p.worst_status()),
-    ]
-    .into_iter()
-    .flatten()
-    {
+    for status in fetched.values().map(|p| p.projection.worst_status()) {
         overall_status = match (overall_status, status) {
             (BudgetStatus::OverBudget, _) => BudgetStatus::OverBudget,
             (_, BudgetStatus::OverBudget) => BudgetStatus::OverBudget,
@@ -346,58 +620,173 @@ fn fetch_and_update(
     }
     eprintln!("[NotifAI] Worst status overall: {:?}", overall_status);
 
-        // Check and send notifications (if enabled)
-        if current_settings.notifications_enabled {
-            let mut notif_guard = notif_state.lock().unwrap();
-            for proj in [projection_claude.as_ref(), projection_codex.as_ref()].into_iter().flatten() {
-                let notifications = check_notifications(
-                    proj,
-                    &notif_guard,
-                    current_settings.notify_approaching_percent,
-                    current_settings.notify_over_budget_percent,
+    // Check and send notifications (if enabled)
+    if current_settings.notifications_enabled {
+        let mut notif_guard = notif_state.lock().unwrap();
+        let mut changed = false;
+
+        // Quiet hours are over: send what was held back
+        let now = Local::now();
+        if notif_guard.snoozed_until(now).is_none() && !current_settings.quiet_hours.is_quiet(now) {
+            if let Some(digest) = notif_guard.take_digest() {
+                alert::dispatch(channels, &digest);
+            }
+        }
+
+        for provider_state in fetched.values() {
+            let notifications = rules::check_rules(
+                &provider_state.projection,
+                &current_settings.notification_rules,
+                &notif_guard,
+            );
+            for info in notifications {
+                notif_guard.record_notification(
+                    info.quota_type.clone(),
+                    info.severity.clone(),
+                    info.reset_time,
                 );
-                for info in notifications {
-                    let _ = app
-                        .notification()
-                        .builder()
-                        .title(&info.title())
-                        .body(&info.body())
-                        .show();
-                    notif_guard.record_notification(info.quota_type, info.severity, info.reset_time);
-                }
+                deliver(channels, &mut notif_guard, &current_settings.quiet_hours, info);
+                changed = true;
+            }
+        }
+        if notif_guard.prune(Local::now()) || changed {
+            if let Err(e) = notif_guard.save() {
+                eprintln!("[NotifAI] Failed to save notification state: {:#}", e);
+            }
+        }
+    }
+
+    // Compare against the previous fetch for resets and status changes
+    let events: Vec<_> = {
+        let guard = state.lock().unwrap();
+        fetched
+            .iter()
+            .filter_map(|(id, current)| Some((guard.providers.get(id)?, current)))
+            .flat_map(|(previous, current)| {
+                events::detect_events(
+                    (&previous.usage, &previous.projection),
+                    (&current.usage, &current.projection),
+                )
+            })
+            .collect()
+    };
+    for event in &events {
+        eprintln!("[NotifAI] Quota event: {:?}", event);
+        let QuotaEvent::Reset {
+            provider,
+            quota,
+            previous_percent,
+            previous_reset_time,
+            ..
+        } = event
+        else {
+            continue;
+        };
+        if let Some(history) = &state.lock().unwrap().history {
+            if let Err(e) =
+                history.record_period_end(provider, quota, *previous_reset_time, *previous_percent)
+            {
+                eprintln!("[NotifAI] Failed to record closed period: {}", e);
             }
         }
+        if current_settings.notifications_enabled && current_settings.notify_on_reset {
+            let provider_name = &fetched[provider].projection.provider_name;
+            if let Some(info) = event.reset_notification(provider_name) {
+                let mut notif_guard = notif_state.lock().unwrap();
+                deliver(channels, &mut notif_guard, &current_settings.quiet_hours, info);
+            }
+        }
+    }
+    hooks::dispatch_events(&current_settings.hooks, &events);
+
+    // Update state
+    {
+        let mut state_guard = state.lock().unwrap();
+        // Providers that failed keep their last data; disabled ones are dropped
+        state_guard
+            .providers
+            .retain(|id, _| providers.iter().any(|p| p.id() == id));
+        state_guard.providers.extend(fetched);
+        state_guard.last_refresh = Some(Local::now());
+        eprintln!("[NotifAI] State updated successfully");
+    }
+
+    Some(overall_status)
+}
+
+/// Send an alert unless alerts are snoozed; during quiet hours it is held for
+/// the digest (or dropped if the digest is off)
+fn deliver(
+    channels: &[Box<dyn AlertChannel>],
+    notif_state: &mut NotificationState,
+    quiet_hours: &QuietHours,
+    info: NotificationInfo,
+) {
+    let now = Local::now();
+    if notif_state.snoozed_until(now).is_some() {
+        eprintln!("[NotifAI] Alerts snoozed, skipping: {}", info.title());
+    } else if quiet_hours.is_quiet(now) {
+        eprintln!("[NotifAI] Quiet hours, holding back: {}", info.title());
+        if quiet_hours.digest {
+            notif_state.queue(info);
+        }
+    } else {
+        alert::dispatch(channels, &info);
+    }
+}
 
-        // Update state
+/// Fetch usage and update state, tray menu and icon
+fn fetch_and_update(
+    app: &AppHandle,
+    state: &Arc<Mutex<AppState>>,
+    notif_state: &Arc<Mutex<NotificationState>>,
+) {
+    eprintln!("[NotifAI] fetch_and_update called");
+
+    let mut channels: Vec<Box<dyn AlertChannel>> =
+        vec![Box::new(DesktopChannel { app: app.clone() })];
+    channels.extend(alert::configured_channels(&state.lock().unwrap().settings));
+    let overall_status = refresh(state, notif_state, &channels);
+
+    // Update menu and icon even if every fetch failed, to flag the stale data
+    update_menu(app, state, notif_state);
+    if let Some(tray) = app.tray_by_id("main") {
+        let stale = state.lock().unwrap().is_stale();
+        update_tray_icon(&tray, overall_status.unwrap_or(BudgetStatus::Unknown), stale);
+        eprintln!("[NotifAI] Menu and icon updated");
+    }
+}
+
+/// Run `on_tick` every refresh interval (read from settings each cycle), skipping
+/// cycles while a refresh is already in progress. Never returns.
+fn refresh_loop(state: Arc<Mutex<AppState>>, on_tick: impl Fn()) {
+    loop {
+        // Get current interval from settings
+        let interval_minutes = {
+            let guard = state.lock().unwrap();
+            guard.settings.refresh_interval_minutes
+        };
+        let interval = Duration::from_secs(interval_minutes * 60);
+
+        thread::sleep(interval);
+
+        // Check if already refreshing
         {
-            let mut state_guard = state.lock().unwrap();
-            state_guard.usage_claude = claude_usage.ok();
-            state_guard.usage_codex = codex_usage.ok();
-            state_guard.projection_claude = projection_claude;
-            state_guard.projection_codex = projection_codex;
-            state_guard.last_refresh = Some(Local::now());
-            eprintln!("[NotifAI] State updated successfully");
+            let state_guard = state.lock().unwrap();
+            if state_guard.is_refreshing.swap(true, Ordering::SeqCst) {
+                continue; // Skip this cycle if already refreshing
+            }
         }
 
-        // Update menu
-        let state_guard = state.lock().unwrap();
-        eprintln!(
-            "[NotifAI] Building menu with state: usage_claude={}, usage_codex={}, proj_claude={}, proj_codex={}",
-            state_guard.usage_claude.is_some(),
-            state_guard.usage_codex.is_some(),
-            state_guard.projection_claude.is_some(),
-            state_guard.projection_codex.is_some()
-        );
-        let menu = build_usage_menu(app, &state_guard);
-        if let Some(tray) = app.tray_by_id("main") {
-            let _ = tray.set_menu(Some(menu));
-            // Update icon based on status
-            update_tray_icon(&tray, overall_status);
-            eprintln!("[NotifAI] Menu and icon updated");
-        } else {
-            eprintln!("[NotifAI] ERROR: Could not find tray with id 'main'");
+        // Do the refresh
+        on_tick();
+
+        // Mark as done refreshing
+        {
+            let state_guard = state.lock().unwrap();
+            state_guard.is_refreshing.store(false, Ordering::SeqCst);
         }
-    
+    }
 }
 
 /// Start the auto-refresh background loop
@@ -407,34 +796,43 @@ fn start_auto_refresh(
     notif_state: Arc<Mutex<NotificationState>>,
 ) {
     thread::spawn(move || {
-        loop {
-            // Get current interval from settings
-            let interval_minutes = {
-                let guard = state.lock().unwrap();
-                guard.settings.refresh_interval_minutes
-            };
-            let interval = Duration::from_secs(interval_minutes * 60);
-
-            thread::sleep(interval);
+        let state_for_tick = state.clone();
+        refresh_loop(state, move || {
+            fetch_and_update(&app, &state_for_tick, &notif_state)
+        });
+    });
+}
 
-            // Check if already refreshing
-            {
-                let state_guard = state.lock().unwrap();
-                if state_guard.is_refreshing.swap(true, Ordering::SeqCst) {
-                    continue; // Skip this cycle if already refreshing
-                }
-            }
+/// Run `on_tick` on a background thread unless a refresh is already in progress.
+/// Returns false if one was.
+fn spawn_refresh(state: &Arc<Mutex<AppState>>, on_tick: impl FnOnce() + Send + 'static) -> bool {
+    if state.lock().unwrap().is_refreshing.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    let state = state.clone();
+    thread::spawn(move || {
+        on_tick();
+        state.lock().unwrap().is_refreshing.store(false, Ordering::SeqCst);
+    });
+    true
+}
 
-            // Do the refresh
-            fetch_and_update(&app, &state, &notif_state);
+/// Start the local HTTP API if enabled in settings; `on_refresh` serves `POST /refresh`
+fn start_api(state: &Arc<Mutex<AppState>>, on_refresh: Arc<dyn Fn() + Send + Sync>) {
+    let settings = state.lock().unwrap().settings.clone();
+    if !settings.api_enabled {
+        return;
+    }
 
-            // Mark as done refreshing
-            {
-                let state_guard = state.lock().unwrap();
-                state_guard.is_refreshing.store(false, Ordering::SeqCst);
-            }
-        }
+    let token = Some(settings.api_token).filter(|t| !t.is_empty());
+    let state_for_trigger = state.clone();
+    let trigger: api::RefreshTrigger = Arc::new(move || {
+        let on_refresh = on_refresh.clone();
+        spawn_refresh(&state_for_trigger, move || on_refresh())
     });
+    if let Err(e) = api::start_api_server(state.clone(), settings.api_port, token, trigger) {
+        eprintln!("[NotifAI] Failed to start API: {:#}", e);
+    }
 }
 
 // Tauri commands for settings
@@ -464,6 +862,68 @@ fn save_settings_cmd(
     Ok(())
 }
 
+/// A quota notification rules can target
+#[derive(serde::Serialize)]
+struct QuotaOption {
+    provider: String,
+    provider_name: String,
+    quota: String,
+    /// e.g. "Claude Session"
+    label: String,
+}
+
+#[tauri::command]
+fn get_quota_options(state: tauri::State<'_, Arc<Mutex<AppState>>>) -> Vec<QuotaOption> {
+    let guard = state.lock().unwrap();
+    provider::registry(&guard.settings)
+        .iter()
+        .flat_map(|p| {
+            p.quotas().iter().map(|spec| QuotaOption {
+                provider: p.id().to_string(),
+                provider_name: p.display_name().to_string(),
+                quota: spec.id.to_string(),
+                label: format!("{} {}", p.display_name(), spec.label),
+            })
+        })
+        .collect()
+}
+
+#[tauri::command(async)]
+fn test_webhook(url: String) -> Result<(), String> {
+    webhook::send_test(&url).map_err(|e| format!("{:#}", e))
+}
+
+#[tauri::command]
+fn get_attribution(state: tauri::State<'_, Arc<Mutex<AppState>>>) -> Vec<QuotaAttribution> {
+    attribution::attribute_quotas(&state.lock().unwrap())
+}
+
+// Tauri commands for usage history
+
+#[tauri::command]
+fn get_period_history(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    provider: String,
+    quota: String,
+) -> Result<Vec<Sample>, String> {
+    let guard = state.lock().unwrap();
+    let history = guard.history.as_ref().ok_or("History is not available")?;
+    history
+        .current_period_samples(&provider, &quota)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_history_between(
+    state: tauri::State<'_, Arc<Mutex<AppState>>>,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+) -> Result<Vec<Sample>, String> {
+    let guard = state.lock().unwrap();
+    let history = guard.history.as_ref().ok_or("History is not available")?;
+    history.samples_between(from, to).map_err(|e| e.to_string())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let app_state: Arc<Mutex<AppState>> = Arc::new(Mutex::new(AppState::new()));
@@ -478,7 +938,15 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_notification::init())
         .manage(state_for_invoke)
-        .invoke_handler(tauri::generate_handler![get_settings, save_settings_cmd])
+        .invoke_handler(tauri::generate_handler![
+            get_settings,
+            save_settings_cmd,
+            get_quota_options,
+            get_attribution,
+            test_webhook,
+            get_period_history,
+            get_history_between
+        ])
         .setup(move |app| {
             // Hide from dock on macOS
             #[cfg(target_os = "macos")]
@@ -488,9 +956,32 @@ pub fn run() {
 
             // Load settings from store
             let loaded_settings = load_settings(&app_handle);
+
+            // Open usage history under the app data dir
+            let history = match app.path().app_data_dir() {
+                Ok(dir) => match HistoryStore::open(&dir.join("history.db")) {
+                    Ok(store) => Some(store),
+                    Err(e) => {
+                        eprintln!("[NotifAI] Failed to open history: {:#}", e);
+                        None
+                    }
+                },
+                Err(e) => {
+                    eprintln!("[NotifAI] Could not resolve app data dir: {}", e);
+                    None
+                }
+            };
+
             {
                 let mut guard = state_for_setup.lock().unwrap();
                 guard.settings = loaded_settings;
+                guard.history = history;
+            }
+
+            // Restore sent notifications so a restart doesn't repeat them
+            if let Ok(dir) = app.path().app_data_dir() {
+                *notif_for_setup.lock().unwrap() =
+                    NotificationState::open(&dir.join(notification::STATE_FILE));
             }
 
             let state = state_for_setup.clone();
@@ -498,7 +989,7 @@ pub fn run() {
 
             // Build initial tray with loading state
             let initial_state = state.lock().unwrap();
-            let menu = build_usage_menu(&app_handle, &initial_state);
+            let menu = build_usage_menu(&app_handle, &initial_state, None);
             drop(initial_state);
 
             let state_for_events = state.clone();
@@ -518,13 +1009,31 @@ pub fn run() {
                         let state = state_for_events.clone();
                         let notif = notif_for_events.clone();
                         let app = app.clone();
-                        thread::spawn(move || {
+                        spawn_refresh(&state_for_events, move || {
                             fetch_and_update(&app, &state, &notif);
                         });
                     }
+                    "snooze_1h" | "snooze_reset" | "snooze_off" => {
+                        let until = match event.id.as_ref() {
+                            "snooze_1h" => Some(Local::now() + chrono::Duration::hours(1)),
+                            "snooze_reset" => next_reset(&state_for_events.lock().unwrap()),
+                            _ => None,
+                        };
+                        {
+                            let mut notif = notif_for_events.lock().unwrap();
+                            match until {
+                                Some(until) => notif.snooze(until),
+                                None => notif.resume(),
+                            }
+                        }
+                        update_menu(app, &state_for_events, &notif_for_events);
+                    }
                     "settings" => {
                         open_settings_window(&app_for_events);
                     }
+                    "dashboard" => {
+                        open_dashboard_window(&app_for_events);
+                    }
                     "about" => {
                         open_about_window(&app_for_events);
                     }
@@ -546,6 +1055,17 @@ pub fn run() {
             let notif_for_refresh = notif.clone();
             start_auto_refresh(app_handle_for_refresh, state_for_refresh, notif_for_refresh);
 
+            // Local HTTP API (opt-in)
+            let app_handle_for_api = app.handle().clone();
+            let state_for_api = state.clone();
+            let notif_for_api = notif.clone();
+            start_api(
+                &state,
+                Arc::new(move || {
+                    fetch_and_update(&app_handle_for_api, &state_for_api, &notif_for_api)
+                }),
+            );
+
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -1,5 +1,44 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+/// Release builds on Windows have no console of their own, so command-line modes
+/// write to the console of the shell that started them
+#[cfg(windows)]
+fn attach_parent_console() {
+    /// `ATTACH_PARENT_PROCESS` from the Windows API
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails harmlessly when there's no parent console or one is already attached
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("status") {
+        attach_parent_console();
+        match notifai_lib::run_status(args) {
+            Ok(code) => std::process::exit(code),
+            Err(e) => {
+                eprintln!("[NotifAI] {:#}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if args.iter().any(|a| a == "--headless") {
+        attach_parent_console();
+        if let Err(e) = notifai_lib::run_headless(args) {
+            eprintln!("[NotifAI] {:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     notifai_lib::run()
 }
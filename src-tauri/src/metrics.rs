@@ -0,0 +1,274 @@
+use chrono::Local;
+use std::fmt::Write;
+
+use crate::projection::{BudgetStatus, ProjectedUsage};
+use crate::provider;
+use crate::AppState;
+
+/// Per-quota gauge: metric name, help text and how to read it from a projection
+type QuotaGauge = (&'static str, &'static str, fn(&ProjectedUsage) -> f64);
+
+/// Every status, exported as one series each so the current one reads 1
+const STATUSES: [BudgetStatus; 4] = [
+    BudgetStatus::UnderBudget,
+    BudgetStatus::OnTrack,
+    BudgetStatus::OverBudget,
+    BudgetStatus::Unknown,
+];
+
+fn status_label(status: BudgetStatus) -> &'static str {
+    match status {
+        BudgetStatus::UnderBudget => "under_budget",
+        BudgetStatus::OnTrack => "on_track",
+        BudgetStatus::OverBudget => "over_budget",
+        BudgetStatus::Unknown => "unknown",
+    }
+}
+
+/// Escape a label value per the Prometheus text format
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Render the Prometheus text exposition (format 0.0.4) of the current state
+pub fn render_metrics(state: &AppState) -> String {
+    let providers = provider::registry(&state.settings);
+    let mut out = String::new();
+
+    // (provider id, projected quota) pairs in menu order
+    let quotas: Vec<_> = providers
+        .iter()
+        .filter_map(|p| state.providers.get(p.id()).map(|s| (p.id(), s)))
+        .flat_map(|(id, s)| s.projection.quotas.iter().map(move |q| (id, q)))
+        .collect();
+
+    let gauges: [QuotaGauge; 3] = [
+        (
+            "notifai_quota_used_percent",
+            "Current quota usage in percent",
+            |p| p.current_percent as f64,
+        ),
+        (
+            "notifai_quota_projected_percent",
+            "Projected quota usage at the end of the period in percent",
+            |p| p.projected_percent as f64,
+        ),
+        (
+            "notifai_quota_reset_seconds",
+            "Seconds until the quota resets",
+            // At scrape time; the projection's own count is from the last fetch
+            |p| (p.reset_at - Local::now()).num_seconds().max(0) as f64,
+        ),
+    ];
+    for (name, help, value) in gauges {
+        header(&mut out, name, "gauge", help);
+        for (provider_id, quota) in &quotas {
+            let _ = writeln!(
+                out,
+                "{}{{provider=\"{}\",quota=\"{}\",label=\"{}\"}} {}",
+                name,
+                escape(provider_id),
+                escape(&quota.id),
+                escape(&quota.label),
+                value(&quota.projected)
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "notifai_quota_status",
+        "gauge",
+        "Budget status of the quota (1 for the current status)",
+    );
+    for (provider_id, quota) in &quotas {
+        for status in STATUSES {
+            let _ = writeln!(
+                out,
+                "notifai_quota_status{{provider=\"{}\",quota=\"{}\",status=\"{}\"}} {}",
+                escape(provider_id),
+                escape(&quota.id),
+                status_label(status),
+                (quota.projected.status == status) as u8
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "notifai_last_fetch_timestamp_seconds",
+        "gauge",
+        "Unix time of the last successful fetch",
+    );
+    for provider in &providers {
+        let last_success = state
+            .fetch_stats
+            .get(provider.id())
+            .and_then(|s| s.last_success);
+        if let Some(at) = last_success {
+            let _ = writeln!(
+                out,
+                "notifai_last_fetch_timestamp_seconds{{provider=\"{}\"}} {}",
+                escape(provider.id()),
+                at.timestamp()
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "notifai_fetch_failures_total",
+        "counter",
+        "Failed fetches since startup",
+    );
+    for provider in &providers {
+        let failures = state
+            .fetch_stats
+            .get(provider.id())
+            .map_or(0, |s| s.failures_total);
+        let _ = writeln!(
+            out,
+            "notifai_fetch_failures_total{{provider=\"{}\"}} {}",
+            escape(provider.id()),
+            failures
+        );
+    }
+
+    header(
+        &mut out,
+        "notifai_fetch_consecutive_failures",
+        "gauge",
+        "Failed fetches since the last success (0 when the data is current)",
+    );
+    for provider in &providers {
+        let failures = state
+            .fetch_stats
+            .get(provider.id())
+            .map_or(0, |s| s.consecutive_failures);
+        let _ = writeln!(
+            out,
+            "notifai_fetch_consecutive_failures{{provider=\"{}\"}} {}",
+            escape(provider.id()),
+            failures
+        );
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projection::{ProjectedQuota, ProjectionMethod, QuotaProjection};
+    use crate::usage::UsageData;
+    use crate::provider::FetchError;
+    use crate::{FetchStats, ProviderState};
+    use chrono::{DateTime, Duration};
+
+    /// State with Claude's weekly quota projected at the last fetch
+    fn fetched_state(time_remaining_secs: i64, reset_at: DateTime<Local>) -> AppState {
+        let mut state = AppState::new();
+        state.providers.insert(
+            "claude".to_string(),
+            ProviderState {
+                usage: UsageData::new(),
+                projection: QuotaProjection {
+                    provider_id: "claude".to_string(),
+                    provider_name: "Claude".to_string(),
+                    quotas: vec![ProjectedQuota {
+                        id: "week_all_models".to_string(),
+                        label: "Week (all models)".to_string(),
+                        projected: ProjectedUsage {
+                            current_percent: 57.0,
+                            projected_percent: 120.5,
+                            status: BudgetStatus::OverBudget,
+                            time_remaining_secs,
+                            reset_at,
+                            method: ProjectionMethod::Linear,
+                            exhaustion_at: None,
+                        },
+                    }],
+                },
+            },
+        );
+        state
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        // A minute of slack so the reset is still 3600s away when scraped
+        let mut state = fetched_state(3600, Local::now() + Duration::seconds(3600 + 60));
+        state.fetch_stats.insert(
+            "claude".to_string(),
+            FetchStats {
+                last_success: Some(Local::now()),
+                ..FetchStats::default()
+            },
+        );
+        state.fetch_stats.insert(
+            "codex".to_string(),
+            FetchStats {
+                last_success: None,
+                failures_total: 3,
+                consecutive_failures: 2,
+                last_error: Some("Rate limited".to_string()),
+                last_cause: Some(FetchError::RateLimited),
+            },
+        );
+
+        let text = render_metrics(&state);
+        let labels = r#"provider="claude",quota="week_all_models",label="Week (all models)""#;
+        assert!(text.contains(&format!("notifai_quota_used_percent{{{}}} 57\n", labels)));
+        assert!(text.contains(&format!(
+            "notifai_quota_projected_percent{{{}}} 120.5\n",
+            labels
+        )));
+        assert!(text.contains(&format!("notifai_quota_reset_seconds{{{}}} 36", labels)));
+        assert!(text.contains(
+            r#"notifai_quota_status{provider="claude",quota="week_all_models",status="over_budget"} 1"#
+        ));
+        assert!(text.contains(
+            r#"notifai_quota_status{provider="claude",quota="week_all_models",status="on_track"} 0"#
+        ));
+        assert!(text.contains(r#"notifai_last_fetch_timestamp_seconds{provider="claude"}"#));
+        assert!(!text.contains(r#"notifai_last_fetch_timestamp_seconds{provider="codex"}"#));
+        assert!(text.contains("notifai_fetch_failures_total{provider=\"codex\"} 3\n"));
+        assert!(text.contains("# TYPE notifai_fetch_failures_total counter\n"));
+        assert!(text.contains("notifai_fetch_consecutive_failures{provider=\"codex\"} 2\n"));
+        assert!(text.contains("notifai_fetch_consecutive_failures{provider=\"claude\"} 0\n"));
+    }
+
+    #[test]
+    fn test_reset_seconds_counted_at_scrape_time() {
+        let reset_seconds = |state: &AppState| -> f64 {
+            let text = render_metrics(state);
+            let line = text
+                .lines()
+                .find(|l| l.starts_with("notifai_quota_reset_seconds{"))
+                .unwrap();
+            line.rsplit(' ').next().unwrap().parse().unwrap()
+        };
+
+        // Fetched an hour ago: two hours were left then, one is left now
+        let state = fetched_state(7200, Local::now() + Duration::hours(1));
+        assert!((3590.0..=3600.0).contains(&reset_seconds(&state)));
+
+        // The reset passed since the last fetch
+        let state = fetched_state(600, Local::now() - Duration::minutes(5));
+        assert_eq!(reset_seconds(&state), 0.0);
+    }
+
+    #[test]
+    fn test_escape_label_values() {
+        assert_eq!(escape(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(escape("x\ny"), "x\\ny");
+    }
+}
@@ -1,35 +1,50 @@
+use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use crate::projection::{ProjectedUsage, QuotaProjection};
+use crate::projection::format_clock_time;
 
-/// Quota type for tracking notifications
+/// File next to settings.json that keeps sent notifications across restarts
+pub const STATE_FILE: &str = "notifications.json";
+
+/// Quota identity for tracking notifications (provider + quota id)
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
-pub enum QuotaType {
-    Session,
-    WeekAll,
-    WeekSonnet,
-    Codex5h,
-    CodexWeek,
+pub struct QuotaType {
+    pub provider: String,
+    pub quota: String,
 }
 
 impl QuotaType {
-    pub fn display_name(&self) -> &'static str {
-        match self {
-            QuotaType::Session => "Session",
-            QuotaType::WeekAll => "Week (all models)",
-            QuotaType::WeekSonnet => "Week (Sonnet)",
-            QuotaType::Codex5h => "Codex 5h",
-            QuotaType::CodexWeek => "Codex week",
+    pub fn new(provider: &str, quota: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            quota: quota.to_string(),
         }
     }
 }
 
 /// Notification severity levels
-#[derive(Debug, Hash, Eq, PartialEq, Clone)]
+#[derive(Debug, Hash, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum NotificationSeverity {
     Approaching, // 100% threshold
     OverBudget,  // 115% threshold
+    Exhaustion,  // projected to hit 100% before the reset
+    Refreshed,   // the period rolled over, usage is available again
+    Digest,      // summary of alerts held during quiet hours
+    FetchFailed, // a provider's fetches keep failing
+}
+
+/// A sent notification as stored in `STATE_FILE`
+#[derive(Serialize, Deserialize)]
+struct SentNotification {
+    provider: String,
+    quota: String,
+    severity: NotificationSeverity,
+    reset_time: DateTime<Local>,
 }
 
 /// Tracks which notifications have been sent to avoid duplicates
@@ -38,6 +53,12 @@ pub struct NotificationState {
     /// Track last notification per quota type and severity
     /// Key: (QuotaType, NotificationSeverity), Value: reset_time when notification was sent
     last_notifications: HashMap<(QuotaType, NotificationSeverity), DateTime<Local>>,
+    /// Where `save` writes to; None keeps the state in memory only
+    path: Option<PathBuf>,
+    /// Alerts are recorded but not delivered until then
+    snoozed_until: Option<DateTime<Local>>,
+    /// Alerts held during quiet hours, for the digest
+    queued: Vec<NotificationInfo>,
 }
 
 impl NotificationState {
@@ -45,6 +66,54 @@ impl NotificationState {
         Self::default()
     }
 
+    /// Load the state persisted at `path`, dropping expired periods. A missing or
+    /// unreadable file starts empty.
+    pub fn open(path: &Path) -> Self {
+        let sent: Vec<SentNotification> = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+            .and_then(|mut value| serde_json::from_value(value["last_notifications"].take()).ok())
+            .unwrap_or_default();
+
+        let mut state = Self {
+            last_notifications: sent
+                .into_iter()
+                .map(|n| ((QuotaType::new(&n.provider, &n.quota), n.severity), n.reset_time))
+                .collect(),
+            path: Some(path.to_path_buf()),
+            ..Self::default()
+        };
+        state.prune(Local::now());
+        state
+    }
+
+    /// Write the state to its file, if it has one
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let sent: Vec<SentNotification> = self
+            .last_notifications
+            .iter()
+            .map(|((quota, severity), reset_time)| SentNotification {
+                provider: quota.provider.clone(),
+                quota: quota.quota.clone(),
+                severity: severity.clone(),
+                reset_time: *reset_time,
+            })
+            .collect();
+        let text = serde_json::to_string_pretty(&json!({ "last_notifications": sent }))?;
+        std::fs::write(path, text)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Forget notifications whose period has reset; returns whether any were removed
+    pub fn prune(&mut self, now: DateTime<Local>) -> bool {
+        let before = self.last_notifications.len();
+        self.last_notifications.retain(|_, reset_time| *reset_time > now);
+        self.last_notifications.len() != before
+    }
+
     /// Check if we should notify for this quota/severity combination
     /// We only notify once per reset period
     pub fn should_notify(
@@ -63,6 +132,43 @@ impl NotificationState {
         }
     }
 
+    /// Suppress delivery until `until`
+    pub fn snooze(&mut self, until: DateTime<Local>) {
+        self.snoozed_until = Some(until);
+    }
+
+    pub fn resume(&mut self) {
+        self.snoozed_until = None;
+    }
+
+    /// End of the snooze, if one is active at `now`
+    pub fn snoozed_until(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.snoozed_until.filter(|until| *until > now)
+    }
+
+    /// Hold an alert for the digest
+    pub fn queue(&mut self, info: NotificationInfo) {
+        self.queued.push(info);
+    }
+
+    /// One notification summarizing the held alerts, if any
+    pub fn take_digest(&mut self) -> Option<NotificationInfo> {
+        if self.queued.is_empty() {
+            return None;
+        }
+        let queued = std::mem::take(&mut self.queued);
+        Some(NotificationInfo {
+            quota_type: QuotaType::new("notifai", "digest"),
+            label: "NotifAI".to_string(),
+            severity: NotificationSeverity::Digest,
+            projected_percent: queued.iter().map(|n| n.projected_percent).fold(0.0, f32::max),
+            reset_time: queued.iter().map(|n| n.reset_time).min().unwrap_or_else(Local::now),
+            exhaustion_at: queued.iter().filter_map(|n| n.exhaustion_at).min(),
+            digest: queued.iter().map(|n| n.title()).collect(),
+            detail: None,
+        })
+    }
+
     /// Record that we sent a notification
     pub fn record_notification(
         &mut self,
@@ -75,81 +181,82 @@ impl NotificationState {
     }
 }
 
-/// Check all quotas and return notifications that should be sent
-pub fn check_notifications(
-    projection: &QuotaProjection,
-    state: &NotificationState,
-    approaching_threshold: f32,
-    over_budget_threshold: f32,
-) -> Vec<NotificationInfo> {
-    let mut notifications = Vec::new();
-
-    // Helper to check a single quota
-    let mut check_quota = |quota_type: QuotaType, proj: &Option<ProjectedUsage>| {
-        if let Some(p) = proj {
-            // We need reset_time to track notifications per reset period
-            // Using projected time as proxy (it's derived from reset_time)
-            let now = Local::now();
-            // Approximate reset_time from time_remaining_secs
-            let reset_time = now + chrono::Duration::seconds(p.time_remaining_secs);
-
-            // Check over budget - higher priority, check first
-            if p.projected_percent >= over_budget_threshold {
-                if state.should_notify(&quota_type, &NotificationSeverity::OverBudget, reset_time) {
-                    notifications.push(NotificationInfo {
-                        quota_type: quota_type.clone(),
-                        severity: NotificationSeverity::OverBudget,
-                        projected_percent: p.projected_percent,
-                        reset_time,
-                    });
-                }
-            }
-            // Check approaching
-            else if p.projected_percent >= approaching_threshold {
-                if state.should_notify(&quota_type, &NotificationSeverity::Approaching, reset_time)
-                {
-                    notifications.push(NotificationInfo {
-                        quota_type: quota_type.clone(),
-                        severity: NotificationSeverity::Approaching,
-                        projected_percent: p.projected_percent,
-                        reset_time,
-                    });
-                }
-            }
-        }
-    };
-
-    check_quota(QuotaType::Session, &projection.session);
-    check_quota(QuotaType::WeekAll, &projection.week_all);
-    check_quota(QuotaType::WeekSonnet, &projection.week_sonnet);
-    check_quota(QuotaType::Codex5h, &projection.codex_five_hour);
-    check_quota(QuotaType::CodexWeek, &projection.codex_week);
-
-    notifications
-}
-
 /// Information about a notification to send
 pub struct NotificationInfo {
     pub quota_type: QuotaType,
+    /// Display label, e.g. "Claude Session"
+    pub label: String,
     pub severity: NotificationSeverity,
+    /// Projected percent at the end of the period (current usage for `Refreshed`)
     pub projected_percent: f32,
     pub reset_time: DateTime<Local>,
+    pub exhaustion_at: Option<DateTime<Local>>,
+    /// Titles of the alerts summarized by a `Digest`
+    pub digest: Vec<String>,
+    /// Body of alerts that aren't about usage, e.g. the error behind `FetchFailed`
+    pub detail: Option<String>,
 }
 
 impl NotificationInfo {
+    /// Alert that a provider failed `failures` fetches in a row; its quotas are
+    /// shown from the last successful fetch until one succeeds again
+    pub fn fetch_failed(
+        provider_id: &str,
+        provider_name: &str,
+        failures: u32,
+        error: &str,
+    ) -> Self {
+        Self {
+            quota_type: QuotaType::new(provider_id, "fetch"),
+            label: provider_name.to_string(),
+            severity: NotificationSeverity::FetchFailed,
+            projected_percent: 0.0,
+            reset_time: Local::now(),
+            exhaustion_at: None,
+            digest: Vec::new(),
+            detail: Some(format!("{} failed fetches in a row: {}", failures, error)),
+        }
+    }
+
     pub fn title(&self) -> String {
         match self.severity {
             NotificationSeverity::Approaching => {
-                format!("{} Approaching Budget", self.quota_type.display_name())
+                format!("{} Approaching Budget", self.label)
             }
             NotificationSeverity::OverBudget => {
-                format!("{} Over Budget", self.quota_type.display_name())
+                format!("{} Over Budget", self.label)
+            }
+            NotificationSeverity::Exhaustion => {
+                format!("{} Will Run Out", self.label)
+            }
+            NotificationSeverity::Refreshed => {
+                format!("{} Quota Refreshed", self.label)
+            }
+            NotificationSeverity::Digest => {
+                format!("{} alerts during quiet hours", self.digest.len())
+            }
+            NotificationSeverity::FetchFailed => {
+                format!("{} Usage Unavailable", self.label)
             }
         }
     }
 
     pub fn body(&self) -> String {
-        format!("Projected {}% usage at end of period", self.projected_percent as i32)
+        match (&self.severity, self.exhaustion_at) {
+            (NotificationSeverity::Exhaustion, Some(exhaustion_at)) => format!(
+                "You'll be locked out at {}, reset is at {}",
+                format_clock_time(exhaustion_at),
+                format_clock_time(self.reset_time)
+            ),
+            (NotificationSeverity::Digest, _) => self.digest.join("\n"),
+            (NotificationSeverity::FetchFailed, _) => self.detail.clone().unwrap_or_default(),
+            (NotificationSeverity::Refreshed, _) => format!(
+                "{}% used, next reset at {}",
+                self.projected_percent as i32,
+                format_clock_time(self.reset_time)
+            ),
+            _ => format!("Projected {}% usage at end of period", self.projected_percent as i32),
+        }
     }
 }
 
@@ -160,25 +267,26 @@ mod tests {
     #[test]
     fn test_notification_state_tracks_correctly() {
         let mut state = NotificationState::new();
+        let session = QuotaType::new("claude", "session");
         let reset_time = Local::now() + chrono::Duration::hours(2);
 
         // Should notify first time
         assert!(state.should_notify(
-            &QuotaType::Session,
+            &session,
             &NotificationSeverity::Approaching,
             reset_time
         ));
 
         // Record notification
         state.record_notification(
-            QuotaType::Session,
+            session.clone(),
             NotificationSeverity::Approaching,
             reset_time,
         );
 
         // Should NOT notify again for same reset period
         assert!(!state.should_notify(
-            &QuotaType::Session,
+            &session,
             &NotificationSeverity::Approaching,
             reset_time
         ));
@@ -186,9 +294,73 @@ mod tests {
         // Should notify for different reset time (new period)
         let new_reset = reset_time + chrono::Duration::hours(5);
         assert!(state.should_notify(
-            &QuotaType::Session,
+            &session,
             &NotificationSeverity::Approaching,
             new_reset
         ));
     }
+
+    #[test]
+    fn test_state_persists_and_prunes_expired_periods() {
+        let path = std::env::temp_dir().join(format!("notifai-state-{}.json", std::process::id()));
+        let session = QuotaType::new("claude", "session");
+        let week = QuotaType::new("claude", "week_all_models");
+        let current_reset = Local::now() + chrono::Duration::hours(2);
+        let expired_reset = Local::now() - chrono::Duration::hours(1);
+
+        let mut state = NotificationState::open(&path);
+        state.record_notification(session.clone(), NotificationSeverity::OverBudget, current_reset);
+        state.record_notification(week.clone(), NotificationSeverity::Approaching, expired_reset);
+        state.save().unwrap();
+
+        let reopened = NotificationState::open(&path);
+        std::fs::remove_file(&path).unwrap();
+        let over = NotificationSeverity::OverBudget;
+        assert!(!reopened.should_notify(&session, &over, current_reset));
+        assert!(reopened.should_notify(&week, &NotificationSeverity::Approaching, expired_reset));
+        assert_eq!(reopened.last_notifications.len(), 1);
+    }
+
+    #[test]
+    fn test_snooze_and_digest() {
+        let mut state = NotificationState::new();
+        let now = Local::now();
+        state.snooze(now + chrono::Duration::hours(1));
+        assert!(state.snoozed_until(now).is_some());
+        assert!(state.snoozed_until(now + chrono::Duration::hours(2)).is_none());
+        state.resume();
+        assert!(state.snoozed_until(now).is_none());
+
+        assert!(state.take_digest().is_none());
+        for (quota, severity) in [
+            ("session", NotificationSeverity::OverBudget),
+            ("week_all_models", NotificationSeverity::Approaching),
+        ] {
+            state.queue(NotificationInfo {
+                quota_type: QuotaType::new("claude", quota),
+                label: format!("Claude {}", quota),
+                severity,
+                projected_percent: 120.0,
+                reset_time: now,
+                exhaustion_at: None,
+                digest: Vec::new(),
+                detail: None,
+            });
+        }
+        let digest = state.take_digest().unwrap();
+        assert_eq!(digest.title(), "2 alerts during quiet hours");
+        assert_eq!(
+            digest.body(),
+            "Claude session Over Budget\nClaude week_all_models Approaching Budget"
+        );
+        assert!(state.take_digest().is_none());
+    }
+
+    #[test]
+    fn test_fetch_failed_notification() {
+        let info = NotificationInfo::fetch_failed("codex", "Codex", 3, "codex exited with 1");
+        assert_eq!(info.title(), "Codex Usage Unavailable");
+        assert_eq!(info.body(), "3 failed fetches in a row: codex exited with 1");
+        assert_eq!(info.quota_type, QuotaType::new("codex", "fetch"));
+    }
 }
@@ -1,9 +1,20 @@
 use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 use chrono_tz::Tz;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::usage::UsageData;
+use crate::history::Sample;
+use crate::provider::UsageProvider;
+use crate::schedule::WorkSchedule;
+use crate::usage::{Quota, UsageData};
+
+/// Number of most recent sample intervals used to estimate the burn rate
+const BURN_RATE_WINDOW: usize = 12;
+/// EWMA smoothing factor (weight given to the newest interval)
+const BURN_RATE_ALPHA: f64 = 0.5;
+/// Minimum time covered by samples before the burn rate is trusted
+const BURN_RATE_MIN_SPAN_SECS: i64 = 15 * 60;
 
 /// Budget status based on projected usage
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
@@ -27,21 +38,35 @@ impl BudgetStatus {
 }
 
 /// Period type for quota calculations
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum PeriodType {
     Session, // 5-hour rolling window
+    Daily,   // 24-hour window
     Weekly,  // 7-day window
 }
 
 impl PeriodType {
-    fn duration(&self) -> Duration {
+    pub fn duration(&self) -> Duration {
         match self {
             PeriodType::Session => Duration::hours(5),
+            PeriodType::Daily => Duration::days(1),
             PeriodType::Weekly => Duration::days(7),
         }
     }
 }
 
+/// How a projection was computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectionMethod {
+    /// Average rate since the start of the period, extrapolated to its end
+    Linear,
+    /// Recent consumption rate (EWMA over stored samples), extrapolated to the end
+    BurnRate,
+    /// Average rate per working hour, extrapolated over the remaining working hours
+    WorkHours,
+}
+
 /// Projected usage for a single quota
 #[derive(Debug, Clone, Serialize)]
 pub struct ProjectedUsage {
@@ -49,6 +74,11 @@ pub struct ProjectedUsage {
     pub projected_percent: f32,
     pub status: BudgetStatus,
     pub time_remaining_secs: i64,
+    /// Parsed reset instant; identifies the period across refreshes
+    pub reset_at: DateTime<Local>,
+    pub method: ProjectionMethod,
+    /// When the quota is estimated to hit 100% (only if before the reset)
+    pub exhaustion_at: Option<DateTime<Local>>,
 }
 
 impl ProjectedUsage {
@@ -58,30 +88,49 @@ impl ProjectedUsage {
     }
 }
 
-/// Collection of projections for all quota types
+/// Projection for a single quota of a provider
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectedQuota {
+    pub id: String,
+    pub label: String,
+    pub projected: ProjectedUsage,
+}
+
+impl ProjectedQuota {
+    /// One-line summary as shown in the tray menu, e.g.
+    /// "◐ Session: 40% → 95% (resets in 2h 10m, runs out at 3:40pm)"
+    pub fn summary(&self) -> String {
+        let p = &self.projected;
+        let runs_out = p
+            .exhaustion_at
+            .map(|at| format!(", runs out at {}", format_clock_time(at)))
+            .unwrap_or_default();
+        format!(
+            "{} {}: {}% → {}% (resets in {}{})",
+            p.status.indicator(),
+            self.label,
+            p.current_percent as i32,
+            p.projected_percent as i32,
+            p.format_time_remaining(),
+            runs_out
+        )
+    }
+}
+
+/// Collection of projections for all quotas of a provider
 #[derive(Debug, Clone, Serialize)]
 pub struct QuotaProjection {
-    pub session: Option<ProjectedUsage>,
-    pub week_all: Option<ProjectedUsage>,
-    pub week_sonnet: Option<ProjectedUsage>,
-    // Codex quotas
-    pub codex_five_hour: Option<ProjectedUsage>,
-    pub codex_week: Option<ProjectedUsage>,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub quotas: Vec<ProjectedQuota>,
 }
 
 impl QuotaProjection {
     /// Returns the worst status across all quotas
     pub fn worst_status(&self) -> BudgetStatus {
-        [
-            &self.session,
-            &self.week_all,
-            &self.week_sonnet,
-            &self.codex_five_hour,
-            &self.codex_week,
-        ]
+        self.quotas
             .iter()
-            .filter_map(|p| p.as_ref())
-            .map(|p| p.status)
+            .map(|q| q.projected.status)
             .max_by_key(|s| match s {
                 BudgetStatus::OverBudget => 3,
                 BudgetStatus::OnTrack => 2,
@@ -97,6 +146,7 @@ impl QuotaProjection {
 /// - "7pm (America/Sao_Paulo)" - without minutes
 /// - "Dec 8 at 3:59pm (America/Sao_Paulo)"
 /// - "Dec 8 at 4pm (America/Sao_Paulo)" - without minutes
+/// - "2025-12-08 15:45" - local date and 24h time
 pub fn parse_reset_time(reset_str: &str) -> Option<DateTime<Local>> {
     // Pattern 1: Time only "6:59pm (timezone)" or "7pm (timezone)" - minutes optional
     let time_only_re =
@@ -111,6 +161,15 @@ pub fn parse_reset_time(reset_str: &str) -> Option<DateTime<Local>> {
     // Pattern 3: 24-hour local time without timezone, e.g. "resets 13:35" or "13:35"
     let time_24_local_re = Regex::new(r"(?i)(?:resets\s+)?(\d{1,2}):(\d{2})").ok()?;
 
+    // Pattern 4: Local date and 24h time, e.g. "2025-12-08 15:45"
+    let local_date_time_re = Regex::new(r"(\d{4}-\d{2}-\d{2} \d{1,2}:\d{2})").ok()?;
+
+    // A full date is taken as is, even if it has passed
+    if let Some(caps) = local_date_time_re.captures(reset_str) {
+        let naive_dt = NaiveDateTime::parse_from_str(caps.get(1)?.as_str(), "%Y-%m-%d %H:%M").ok()?;
+        return Local.from_local_datetime(&naive_dt).single();
+    }
+
     // Try date+time format first (more specific)
     if let Some(caps) = date_time_re.captures(reset_str) {
         let month_str = caps.get(1)?.as_str();
@@ -268,6 +327,9 @@ pub fn calculate_projection(
             projected_percent: current_percent,
             status,
             time_remaining_secs: time_remaining.num_seconds().max(0),
+            reset_at: reset_time,
+            method: ProjectionMethod::Linear,
+            exhaustion_at: None,
         };
     }
 
@@ -282,6 +344,9 @@ pub fn calculate_projection(
                 projected_percent: 0.0,
                 status: BudgetStatus::Unknown,
                 time_remaining_secs: time_remaining.num_seconds(),
+                reset_at: reset_time,
+                method: ProjectionMethod::Linear,
+                exhaustion_at: None,
             };
         }
         // Otherwise, project as 0% (under budget)
@@ -290,6 +355,9 @@ pub fn calculate_projection(
             projected_percent: 0.0,
             status: BudgetStatus::UnderBudget,
             time_remaining_secs: time_remaining.num_seconds(),
+            reset_at: reset_time,
+            method: ProjectionMethod::Linear,
+            exhaustion_at: None,
         };
     }
 
@@ -304,134 +372,250 @@ pub fn calculate_projection(
             projected_percent: current_percent,
             status: BudgetStatus::Unknown,
             time_remaining_secs: time_remaining.num_seconds(),
+            reset_at: reset_time,
+            method: ProjectionMethod::Linear,
+            exhaustion_at: None,
         };
     }
 
     let projected_percent = (current_percent as f64 * total_secs / elapsed_secs) as f32;
+    let rate = current_percent as f64 / elapsed_secs;
+
+    ProjectedUsage {
+        current_percent,
+        projected_percent,
+        status: status_for(projected_percent, threshold_under_budget, threshold_over_budget),
+        time_remaining_secs: time_remaining.num_seconds(),
+        reset_at: reset_time,
+        method: ProjectionMethod::Linear,
+        exhaustion_at: exhaustion_time(now, current_percent, rate, reset_time),
+    }
+}
 
-    // Determine status based on projected percentage and configurable thresholds
-    let status = if projected_percent < threshold_under_budget {
+/// When usage growing at `rate` (percent per second) reaches 100%, if that
+/// happens before the reset
+fn exhaustion_time(
+    now: DateTime<Local>,
+    current_percent: f32,
+    rate: f64,
+    reset_time: DateTime<Local>,
+) -> Option<DateTime<Local>> {
+    if rate <= 0.0 || current_percent >= 100.0 {
+        return None;
+    }
+    let secs_to_full = (100.0 - current_percent as f64) / rate;
+    let at = now + Duration::seconds(secs_to_full as i64);
+    (at < reset_time).then_some(at)
+}
+
+/// Determine status based on projected percentage and configurable thresholds
+fn status_for(
+    projected_percent: f32,
+    threshold_under_budget: f32,
+    threshold_over_budget: f32,
+) -> BudgetStatus {
+    if projected_percent < threshold_under_budget {
         BudgetStatus::UnderBudget
     } else if projected_percent <= threshold_over_budget {
         BudgetStatus::OnTrack
     } else {
         BudgetStatus::OverBudget
-    };
+    }
+}
 
-    ProjectedUsage {
+/// Estimate the recent consumption rate in percent per second, as an EWMA over
+/// the last `BURN_RATE_WINDOW` intervals between samples (oldest first).
+/// Returns None if the samples don't cover enough time to be meaningful.
+pub fn estimate_burn_rate(samples: &[Sample]) -> Option<f64> {
+    let window = &samples[samples.len().saturating_sub(BURN_RATE_WINDOW + 1)..];
+    let (first, last) = (window.first()?, window.last()?);
+    if last.timestamp.signed_duration_since(first.timestamp).num_seconds()
+        < BURN_RATE_MIN_SPAN_SECS
+    {
+        return None;
+    }
+
+    let mut rate: Option<f64> = None;
+    for pair in window.windows(2) {
+        let secs = pair[1].timestamp.signed_duration_since(pair[0].timestamp).num_seconds();
+        if secs <= 0 {
+            continue;
+        }
+        // Usage never goes down within a period; treat drops as no consumption
+        let delta = (pair[1].used_percent - pair[0].used_percent).max(0.0) as f64;
+        let interval_rate = delta / secs as f64;
+        rate = Some(match rate {
+            Some(prev) => BURN_RATE_ALPHA * interval_rate + (1.0 - BURN_RATE_ALPHA) * prev,
+            None => interval_rate,
+        });
+    }
+    rate
+}
+
+/// Calculate projection for a single quota from its recent burn rate.
+/// Returns None when there isn't enough history, so callers can fall back to
+/// `calculate_projection`.
+pub fn calculate_burn_rate_projection(
+    current_percent: f32,
+    reset_time: DateTime<Local>,
+    samples: &[Sample],
+    threshold_under_budget: f32,
+    threshold_over_budget: f32,
+) -> Option<ProjectedUsage> {
+    let now = Local::now();
+    let time_remaining = reset_time.signed_duration_since(now);
+
+    // Near reset the linear projection (current == projected) is already accurate
+    if time_remaining < Duration::minutes(5) {
+        return None;
+    }
+
+    let rate = estimate_burn_rate(samples)?;
+    let remaining_secs = time_remaining.num_seconds() as f64;
+    let projected_percent = (current_percent as f64 + rate * remaining_secs) as f32;
+
+    Some(ProjectedUsage {
         current_percent,
         projected_percent,
-        status,
+        status: status_for(projected_percent, threshold_under_budget, threshold_over_budget),
         time_remaining_secs: time_remaining.num_seconds(),
+        reset_at: reset_time,
+        method: ProjectionMethod::BurnRate,
+        exhaustion_at: exhaustion_time(now, current_percent, rate, reset_time),
+    })
+}
+
+/// Calculate projection for a weekly quota counting only working hours as elapsed
+/// and remaining capacity, so nights and weekends don't dilute the burn rate.
+/// Returns None if no working time has elapsed yet in the period.
+pub fn calculate_work_hours_projection(
+    current_percent: f32,
+    reset_time: DateTime<Local>,
+    period_type: PeriodType,
+    schedule: &WorkSchedule,
+    threshold_under_budget: f32,
+    threshold_over_budget: f32,
+) -> Option<ProjectedUsage> {
+    let now = Local::now();
+    let period_start = reset_time - period_type.duration();
+    let time_remaining = reset_time.signed_duration_since(now);
+
+    // Near reset the linear projection (current == projected) is already accurate
+    if time_remaining < Duration::minutes(5) {
+        return None;
     }
+
+    let active_elapsed = schedule.active_secs_between(period_start, now);
+    let active_remaining = schedule.active_secs_between(now, reset_time);
+    if active_elapsed <= 0 {
+        return None;
+    }
+
+    let rate = current_percent as f64 / active_elapsed as f64;
+    let projected_percent = (current_percent as f64 + rate * active_remaining as f64) as f32;
+
+    let exhaustion_at = if rate > 0.0 && current_percent < 100.0 {
+        let active_to_full = ((100.0 - current_percent as f64) / rate) as i64;
+        schedule
+            .advance(now, active_to_full)
+            .filter(|at| *at < reset_time)
+    } else {
+        None
+    };
+
+    Some(ProjectedUsage {
+        current_percent,
+        projected_percent,
+        status: status_for(projected_percent, threshold_under_budget, threshold_over_budget),
+        time_remaining_secs: time_remaining.num_seconds(),
+        reset_at: reset_time,
+        method: ProjectionMethod::WorkHours,
+        exhaustion_at,
+    })
 }
 
-/// Calculate projections for all quota types from usage data
+/// Calculate projections for every quota in the usage data. Quotas the provider
+/// declares come first (in declaration order), followed by any others it reported.
+///
+/// Weekly quotas use the working-hours projection when the schedule is enabled.
+/// Otherwise `samples` (current-period history per quota id) selects the burn-rate
+/// projection for quotas with enough history; the rest fall back to linear.
 pub fn calculate_all_projections(
+    provider: &dyn UsageProvider,
     usage: &UsageData,
+    samples: &HashMap<String, Vec<Sample>>,
+    schedule: &WorkSchedule,
     threshold_under_budget: f32,
     threshold_over_budget: f32,
 ) -> QuotaProjection {
-    let session = usage
-        .current_session_percent
-        .and_then(|pct| {
-            usage
-                .current_session_reset
-                .as_ref()
-                .and_then(|reset| parse_reset_time(reset))
-                .map(|reset_time| {
-                    calculate_projection(
-                        pct,
-                        reset_time,
-                        PeriodType::Session,
-                        threshold_under_budget,
-                        threshold_over_budget,
-                    )
-                })
-        });
-
-    let week_all = usage
-        .current_week_all_models_percent
-        .and_then(|pct| {
-            usage
-                .current_week_all_models_reset
-                .as_ref()
-                .and_then(|reset| parse_reset_time(reset))
-                .map(|reset_time| {
-                    calculate_projection(
-                        pct,
-                        reset_time,
-                        PeriodType::Weekly,
-                        threshold_under_budget,
-                        threshold_over_budget,
-                    )
-                })
-        });
-
-    let week_sonnet = usage
-        .current_week_sonnet_percent
-        .and_then(|pct| {
-            usage
-                .current_week_sonnet_reset
-                .as_ref()
-                .and_then(|reset| parse_reset_time(reset))
-                .map(|reset_time| {
-                    calculate_projection(
-                        pct,
+    let mut quotas: Vec<&Quota> = usage.quotas.iter().collect();
+    quotas.sort_by_key(|q| {
+        provider
+            .quotas()
+            .iter()
+            .position(|spec| spec.id == q.id)
+            .unwrap_or(usize::MAX)
+    });
+
+    let quotas = quotas
+        .into_iter()
+        .filter_map(|quota| {
+            let reset_time = parse_reset_time(quota.reset.as_ref()?)?;
+            let work_hours = (schedule.enabled && quota.period == PeriodType::Weekly)
+                .then(|| {
+                    calculate_work_hours_projection(
+                        quota.used_percent,
                         reset_time,
-                        PeriodType::Weekly,
+                        quota.period,
+                        schedule,
                         threshold_under_budget,
                         threshold_over_budget,
                     )
                 })
-        });
-
-    // Codex 5h limit: convert \"left\" to \"used\"
-    let codex_five_hour = usage
-        .codex_five_hour_left
-        .map(|left| 100.0 - left)
-        .and_then(|pct_used| {
-            usage
-                .codex_five_hour_reset
-                .as_ref()
-                .and_then(|reset| parse_reset_time(reset))
-                .map(|reset_time| {
-                    calculate_projection(
-                        pct_used,
-                        reset_time,
-                        PeriodType::Session,
-                        threshold_under_budget,
-                        threshold_over_budget,
-                    )
+                .flatten();
+            let projected = work_hours
+                .or_else(|| {
+                    samples.get(&quota.id).and_then(|history| {
+                        calculate_burn_rate_projection(
+                            quota.used_percent,
+                            reset_time,
+                            history,
+                            threshold_under_budget,
+                            threshold_over_budget,
+                        )
+                    })
                 })
-        });
-
-    // Codex weekly limit
-    let codex_week = usage
-        .codex_week_left
-        .map(|left| 100.0 - left)
-        .and_then(|pct_used| {
-            usage
-                .codex_week_reset
-                .as_ref()
-                .and_then(|reset| parse_reset_time(reset))
-                .map(|reset_time| {
+                .unwrap_or_else(|| {
                     calculate_projection(
-                        pct_used,
+                        quota.used_percent,
                         reset_time,
-                        PeriodType::Weekly,
+                        quota.period,
                         threshold_under_budget,
                         threshold_over_budget,
                     )
-                })
-        });
+                });
+            Some(ProjectedQuota {
+                id: quota.id.clone(),
+                label: quota.label.clone(),
+                projected,
+            })
+        })
+        .collect();
 
     QuotaProjection {
-        session,
-        week_all,
-        week_sonnet,
-        codex_five_hour,
-        codex_week,
+        provider_id: provider.id().to_string(),
+        provider_name: provider.display_name().to_string(),
+        quotas,
+    }
+}
+
+/// Format a wall-clock time like "3:40pm", prefixed with the weekday if it's not today
+pub fn format_clock_time(time: DateTime<Local>) -> String {
+    let clock = time.format("%-I:%M%P").to_string();
+    if time.date_naive() == Local::now().date_naive() {
+        clock
+    } else {
+        format!("{} {}", time.format("%a"), clock)
     }
 }
 
@@ -484,6 +668,12 @@ mod tests {
         assert!(result.is_some());
     }
 
+    #[test]
+    fn test_parse_local_date_time() {
+        let result = parse_reset_time("2025-12-08 15:45").unwrap();
+        assert_eq!(result, Local.with_ymd_and_hms(2025, 12, 8, 15, 45, 0).unwrap());
+    }
+
     #[test]
     fn test_status_thresholds() {
         let now = Local::now();
@@ -495,6 +685,147 @@ mod tests {
         assert_eq!(proj.status, BudgetStatus::UnderBudget);
     }
 
+    #[test]
+    fn test_all_projections_follow_provider_quotas() {
+        let quota = |id: &str, pct: f32, reset: &str, period| Quota {
+            id: id.to_string(),
+            label: id.to_string(),
+            used_percent: pct,
+            reset: Some(reset.to_string()),
+            period,
+        };
+        let mut usage = UsageData::new();
+        usage.quotas = vec![
+            quota("week_opus_only", 4.0, "Dec 22 at 3:59pm (America/Sao_Paulo)", PeriodType::Weekly),
+            quota("week_sonnet_only", 8.0, "Dec 22 at 3:59pm (America/Sao_Paulo)", PeriodType::Weekly),
+            quota("session", 17.0, "1:59am (America/Sao_Paulo)", PeriodType::Session),
+        ];
+
+        let proj = calculate_all_projections(
+            &crate::usage::ClaudeProvider::new(None),
+            &usage,
+            &HashMap::new(),
+            &WorkSchedule::default(),
+            85.0,
+            115.0,
+        );
+        let ids: Vec<&str> = proj.quotas.iter().map(|q| q.id.as_str()).collect();
+        assert_eq!(proj.provider_id, "claude");
+        assert_eq!(ids, vec!["session", "week_sonnet_only", "week_opus_only"]);
+        assert!(proj
+            .quotas
+            .iter()
+            .all(|q| q.projected.method == ProjectionMethod::Linear));
+    }
+
+    fn sample_at(minutes_ago: i64, used_percent: f32) -> Sample {
+        Sample {
+            provider: "claude".to_string(),
+            quota: "week_all_models".to_string(),
+            timestamp: Local::now() - Duration::minutes(minutes_ago),
+            used_percent,
+            reset_at: None,
+            period_secs: PeriodType::Weekly.duration().num_seconds(),
+        }
+    }
+
+    #[test]
+    fn test_burn_rate_catches_recent_burst() {
+        // Flat for days, then +20% in the last hour, with 3 days left
+        let reset = Local::now() + Duration::days(3);
+        let samples = vec![
+            sample_at(180, 20.0),
+            sample_at(120, 20.0),
+            sample_at(60, 20.0),
+            sample_at(0, 40.0),
+        ];
+
+        let linear = calculate_projection(40.0, reset, PeriodType::Weekly, 85.0, 115.0);
+        assert_eq!(linear.status, BudgetStatus::UnderBudget);
+
+        let proj = calculate_burn_rate_projection(40.0, reset, &samples, 85.0, 115.0).unwrap();
+        assert_eq!(proj.method, ProjectionMethod::BurnRate);
+        assert_eq!(proj.status, BudgetStatus::OverBudget);
+        let exhaustion = proj.exhaustion_at.unwrap();
+        assert!(exhaustion > Local::now() && exhaustion < reset);
+    }
+
+    #[test]
+    fn test_burn_rate_needs_enough_history() {
+        let reset = Local::now() + Duration::days(3);
+        assert!(calculate_burn_rate_projection(40.0, reset, &[sample_at(0, 40.0)], 85.0, 115.0)
+            .is_none());
+        assert!(estimate_burn_rate(&[sample_at(5, 39.0), sample_at(0, 40.0)]).is_none());
+
+        // Idle usage has a zero burn rate and never exhausts
+        let idle = [sample_at(60, 40.0), sample_at(0, 40.0)];
+        let proj = calculate_burn_rate_projection(40.0, reset, &idle, 85.0, 115.0).unwrap();
+        assert_eq!(proj.projected_percent, 40.0);
+        assert!(proj.exhaustion_at.is_none());
+    }
+
+    #[test]
+    fn test_work_hours_projection() {
+        let reset = Local::now() + Duration::days(3);
+
+        // Round-the-clock schedule matches the linear projection
+        let always = WorkSchedule {
+            enabled: true,
+            days: (0..7).collect(),
+            start_hour: 0,
+            end_hour: 24,
+            timezone: String::new(),
+        };
+        let linear = calculate_projection(40.0, reset, PeriodType::Weekly, 85.0, 115.0);
+        let proj =
+            calculate_work_hours_projection(40.0, reset, PeriodType::Weekly, &always, 85.0, 115.0)
+                .unwrap();
+        assert_eq!(proj.method, ProjectionMethod::WorkHours);
+        assert!((proj.projected_percent - linear.projected_percent).abs() < 0.5);
+
+        // A schedule with no working days never has elapsed working time
+        let never = WorkSchedule {
+            days: Vec::new(),
+            ..always
+        };
+        assert!(calculate_work_hours_projection(
+            40.0,
+            reset,
+            PeriodType::Weekly,
+            &never,
+            85.0,
+            115.0
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_linear_exhaustion_before_reset() {
+        let now = Local::now();
+        let reset = now + Duration::hours(2);
+
+        // 45% after 3h of a 5h window: 15%/h, would need ~3.7h more
+        let proj = calculate_projection(45.0, reset, PeriodType::Session, 85.0, 115.0);
+        assert!(proj.exhaustion_at.is_none());
+
+        // 90% after 3h: 30%/h, hits 100% in ~20 minutes
+        let proj = calculate_projection(90.0, reset, PeriodType::Session, 85.0, 115.0);
+        let exhaustion = proj.exhaustion_at.unwrap();
+        assert!(exhaustion > now + Duration::minutes(15));
+        assert!(exhaustion < now + Duration::minutes(25));
+    }
+
+    #[test]
+    fn test_format_clock_time() {
+        let today = Local::now()
+            .date_naive()
+            .and_hms_opt(15, 40, 0)
+            .and_then(|dt| Local.from_local_datetime(&dt).single())
+            .unwrap();
+        assert_eq!(format_clock_time(today), "3:40pm");
+        assert!(format_clock_time(today + Duration::days(1)).ends_with(" 3:40pm"));
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration_secs(3600), "1h 0m");
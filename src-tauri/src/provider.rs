@@ -0,0 +1,266 @@
+use anyhow::Result;
+use std::fmt;
+
+use crate::codex::CodexProvider;
+use crate::gemini::GeminiProvider;
+use crate::projection::PeriodType;
+use crate::settings::Settings;
+use crate::transcripts::TranscriptScanner;
+use crate::usage::{ClaudeProvider, UsageData};
+
+/// Static description of a quota exposed by a provider
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaSpec {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub period: PeriodType,
+}
+
+/// A source of quota usage data (e.g. a CLI we drive through a PTY)
+pub trait UsageProvider: Send + Sync {
+    /// Stable identifier, used as key for state and notifications
+    fn id(&self) -> &str;
+
+    /// Name shown in the tray menu and notifications
+    fn display_name(&self) -> &str;
+
+    /// Quotas this provider is known to expose, in display order. Fetched data
+    /// may contain additional quotas, which are shown after these.
+    fn quotas(&self) -> &[QuotaSpec];
+
+    /// Fetch current usage data
+    fn fetch(&self) -> Result<UsageData>;
+
+    /// Additional informational menu lines (e.g. extra usage status)
+    fn extra_lines(&self, _usage: &UsageData) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Scanner for the token counts the CLI logs, if it keeps transcripts
+    fn transcripts(&self) -> Option<TranscriptScanner> {
+        None
+    }
+}
+
+/// Why a fetch failed, for failures the user can act on. Providers return it
+/// inside their `anyhow` error; other failures stay untyped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    /// The CLI isn't installed or its configured path is wrong
+    BinaryNotFound { binary: String },
+    /// The CLI wants a login or reports expired credentials
+    NotLoggedIn { login_command: &'static str },
+    /// The CLI never showed its prompt or usage screen
+    Timeout { secs: u64 },
+    /// The CLI's output doesn't contain usage in a known format
+    UnrecognizedOutput,
+    /// The CLI reports too many requests
+    RateLimited,
+    /// The CLI's terminal queries (e.g. the cursor position) went unanswered
+    TerminalUnsupported,
+}
+
+/// Lowercase screen text of login prompts and expired sessions
+const LOGIN_PATTERNS: [&str; 8] = [
+    "please run /login",
+    "select login method",
+    "how would you like to authenticate",
+    "invalid api key",
+    "oauth token has expired",
+    "not logged in",
+    "sign in with chatgpt",
+    "please log in",
+];
+
+/// Lowercase screen text of rate limit errors
+const RATE_LIMIT_PATTERNS: [&str; 4] = [
+    "rate limit",
+    "rate_limit",
+    "too many requests",
+    "resource_exhausted",
+];
+
+impl FetchError {
+    /// Classify a rendered screen that doesn't show usage: a login prompt or a
+    /// rate limit error, if it is one
+    pub fn from_screen(screen: &str, login_command: &'static str) -> Option<Self> {
+        let screen = screen.to_lowercase();
+        if LOGIN_PATTERNS.iter().any(|p| screen.contains(p)) {
+            Some(FetchError::NotLoggedIn { login_command })
+        } else if RATE_LIMIT_PATTERNS.iter().any(|p| screen.contains(p)) {
+            Some(FetchError::RateLimited)
+        } else {
+            None
+        }
+    }
+
+    /// Map a failed PTY spawn to `BinaryNotFound` when the binary is missing
+    pub fn from_spawn(binary: &str, error: anyhow::Error) -> anyhow::Error {
+        let message = error.to_string();
+        let not_found = message.contains("doesn't exist on the filesystem")
+            || error
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound);
+        if not_found {
+            anyhow::Error::new(FetchError::BinaryNotFound {
+                binary: binary.to_string(),
+            })
+        } else {
+            error.context(format!("Failed to spawn {}", binary))
+        }
+    }
+
+    /// Stable name, e.g. "not_logged_in"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FetchError::BinaryNotFound { .. } => "binary_not_found",
+            FetchError::NotLoggedIn { .. } => "not_logged_in",
+            FetchError::Timeout { .. } => "timeout",
+            FetchError::UnrecognizedOutput => "unrecognized_output",
+            FetchError::RateLimited => "rate_limited",
+            FetchError::TerminalUnsupported => "terminal_unsupported",
+        }
+    }
+
+    /// What the user can do about it, shown in the menu
+    pub fn hint(&self) -> String {
+        match self {
+            FetchError::BinaryNotFound { .. } => {
+                "Install the CLI or fix its path in Settings".to_string()
+            }
+            FetchError::NotLoggedIn { login_command } => format!("Run `{}`", login_command),
+            FetchError::Timeout { .. } => {
+                "Run the CLI once in a terminal to finish any setup prompts".to_string()
+            }
+            FetchError::UnrecognizedOutput => {
+                "The CLI may have changed its output; check for a NotifAI update".to_string()
+            }
+            FetchError::RateLimited => {
+                "Wait a few minutes, it retries on the next refresh".to_string()
+            }
+            FetchError::TerminalUnsupported => {
+                "Usually passes on the next refresh; update the CLI if it keeps failing"
+                    .to_string()
+            }
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::BinaryNotFound { binary } => write!(f, "`{}` not found", binary),
+            FetchError::NotLoggedIn { .. } => write!(f, "Not logged in or session expired"),
+            FetchError::Timeout { secs } => {
+                write!(f, "Timed out after {}s waiting for the CLI", secs)
+            }
+            FetchError::UnrecognizedOutput => write!(f, "Usage output not recognized"),
+            FetchError::RateLimited => write!(f, "Rate limited"),
+            FetchError::TerminalUnsupported => {
+                write!(f, "The CLI couldn't read the terminal cursor position")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+/// Build the list of providers to fetch, in display order
+pub fn registry(settings: &Settings) -> Vec<Box<dyn UsageProvider>> {
+    let mut providers: Vec<Box<dyn UsageProvider>> = Vec::new();
+    // Named profiles replace the default Claude account
+    if settings.claude_profiles.is_empty() {
+        providers.push(Box::new(ClaudeProvider::new(None)));
+    }
+    for profile in &settings.claude_profiles {
+        providers.push(Box::new(ClaudeProvider::new(Some(profile))));
+    }
+    providers.push(Box::new(CodexProvider::new(&settings.codex_path)));
+    if settings.gemini_enabled {
+        providers.push(Box::new(GeminiProvider::new(&settings.gemini_path)));
+    }
+    providers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::ClaudeProfile;
+
+    #[test]
+    fn test_registry_has_unique_ids() {
+        let providers = registry(&Settings::default());
+        let ids: Vec<&str> = providers.iter().map(|p| p.id()).collect();
+        assert_eq!(ids, vec!["claude", "codex"]);
+
+        let settings = Settings {
+            gemini_enabled: true,
+            ..Settings::default()
+        };
+        let ids: Vec<String> = registry(&settings).iter().map(|p| p.id().to_string()).collect();
+        assert_eq!(ids, vec!["claude", "codex", "gemini"]);
+
+        // Profiles replace the default account, each with its own section
+        let profile = |name: &str| ClaudeProfile {
+            name: name.to_string(),
+            ..ClaudeProfile::default()
+        };
+        let settings = Settings {
+            claude_profiles: vec![profile("Personal"), profile("Work")],
+            ..Settings::default()
+        };
+        let providers = registry(&settings);
+        let names: Vec<(&str, &str)> = providers
+            .iter()
+            .map(|p| (p.id(), p.display_name()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("claude_personal", "Claude (Personal)"),
+                ("claude_work", "Claude (Work)"),
+                ("codex", "Codex"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fetch_error_from_screen() {
+        let login = "Invalid API key · Please run /login";
+        assert_eq!(
+            FetchError::from_screen(login, "claude login"),
+            Some(FetchError::NotLoggedIn {
+                login_command: "claude login"
+            })
+        );
+        let limited = FetchError::from_screen("Error: 429 Too Many Requests", "codex login");
+        assert_eq!(limited, Some(FetchError::RateLimited));
+        assert_eq!(FetchError::from_screen("Tip: Paste an image", "codex login"), None);
+
+        let hint = FetchError::NotLoggedIn {
+            login_command: "claude login",
+        }
+        .hint();
+        assert_eq!(hint, "Run `claude login`");
+    }
+
+    #[test]
+    fn test_fetch_error_from_spawn() {
+        let missing = anyhow::anyhow!(
+            "Unable to spawn codex because it doesn't exist on the filesystem \
+             and was not found in PATH"
+        );
+        let error = FetchError::from_spawn("codex", missing);
+        assert_eq!(
+            error.downcast_ref::<FetchError>(),
+            Some(&FetchError::BinaryNotFound {
+                binary: "codex".to_string()
+            })
+        );
+        assert_eq!(error.to_string(), "`codex` not found");
+
+        let other = FetchError::from_spawn("codex", anyhow::anyhow!("permission denied"));
+        assert!(other.downcast_ref::<FetchError>().is_none());
+        assert_eq!(other.to_string(), "Failed to spawn codex");
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::{DateTime, Datelike, Local, TimeZone, Timelike};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Quiet time starting on a weekday; it runs past midnight into the next day
+/// when `end_hour` is not after `start_hour` (e.g. 22 to 7)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietRange {
+    /// Weekday the range starts on, 0 = Monday ... 6 = Sunday
+    pub day: u8,
+    /// First quiet hour (0-23)
+    pub start_hour: u32,
+    /// End of the quiet time, exclusive (0-24)
+    pub end_hour: u32,
+}
+
+/// Times when alerts are held back instead of delivered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub enabled: bool,
+    pub ranges: Vec<QuietRange>,
+    /// IANA timezone like "Europe/Berlin"; empty uses the system timezone
+    pub timezone: String,
+    /// Summarize alerts held during quiet hours in one notification when they end
+    pub digest: bool,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ranges: (0..7)
+                .map(|day| QuietRange {
+                    day,
+                    start_hour: 22,
+                    end_hour: 7,
+                })
+                .collect(),
+            timezone: String::new(),
+            digest: true,
+        }
+    }
+}
+
+impl QuietHours {
+    /// Validate the quiet hours and return errors if invalid
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.ranges.iter().any(|r| r.day > 6) {
+            errors.push("Quiet days must be between 0 (Monday) and 6 (Sunday)".to_string());
+        }
+        if self
+            .ranges
+            .iter()
+            .any(|r| r.start_hour > 23 || r.end_hour > 24)
+        {
+            errors.push("Quiet hours must be within 0-24".to_string());
+        }
+        if self.ranges.iter().any(|r| r.start_hour == r.end_hour) {
+            errors.push("Quiet hours must not start and end at the same hour".to_string());
+        }
+        if !self.timezone.trim().is_empty() && self.timezone.trim().parse::<Tz>().is_err() {
+            errors.push(format!("Unknown timezone: {}", self.timezone));
+        }
+
+        errors
+    }
+
+    /// Whether alerts should be held back at `now`
+    pub fn is_quiet(&self, now: DateTime<Local>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match self.timezone.trim().parse::<Tz>() {
+            Ok(tz) => self.quiet_at(&now.with_timezone(&tz)),
+            Err(_) => self.quiet_at(&now),
+        }
+    }
+
+    fn quiet_at<Z: TimeZone>(&self, at: &DateTime<Z>) -> bool {
+        let today = at.weekday().num_days_from_monday() as u8;
+        let yesterday = (today + 6) % 7;
+        let hour = at.hour();
+        self.ranges.iter().any(|r| {
+            if r.end_hour > r.start_hour {
+                r.day == today && hour >= r.start_hour && hour < r.end_hour
+            } else {
+                // Overnight: the evening of its day and the morning after
+                (r.day == today && hour >= r.start_hour)
+                    || (r.day == yesterday && hour < r.end_hour)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32) -> DateTime<Local> {
+        // 2024-01-01 is a Monday
+        let naive = NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, 30, 0)
+            .unwrap();
+        Local.from_local_datetime(&naive).single().unwrap()
+    }
+
+    #[test]
+    fn test_overnight_and_same_day_ranges() {
+        let quiet = QuietHours {
+            enabled: true,
+            ranges: vec![
+                // Friday night into Saturday morning
+                QuietRange {
+                    day: 4,
+                    start_hour: 22,
+                    end_hour: 8,
+                },
+                // Sunday afternoon
+                QuietRange {
+                    day: 6,
+                    start_hour: 13,
+                    end_hour: 18,
+                },
+            ],
+            ..QuietHours::default()
+        };
+
+        assert!(quiet.is_quiet(at(5, 23)));
+        assert!(quiet.is_quiet(at(6, 7)));
+        assert!(!quiet.is_quiet(at(6, 8)));
+        assert!(!quiet.is_quiet(at(5, 21)));
+        // Thursday night isn't covered
+        assert!(!quiet.is_quiet(at(5, 3)));
+        assert!(quiet.is_quiet(at(7, 13)));
+        assert!(!quiet.is_quiet(at(7, 18)));
+
+        let disabled = QuietHours {
+            enabled: false,
+            ..quiet
+        };
+        assert!(!disabled.is_quiet(at(5, 23)));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(QuietHours::default().validate().is_empty());
+        let invalid = QuietHours {
+            ranges: vec![QuietRange {
+                day: 7,
+                start_hour: 9,
+                end_hour: 9,
+            }],
+            timezone: "Mars/Olympus".to_string(),
+            ..QuietHours::default()
+        };
+        assert_eq!(invalid.validate().len(), 3);
+    }
+}
@@ -0,0 +1,351 @@
+use serde::{Deserialize, Serialize};
+
+use crate::notification::{NotificationInfo, NotificationSeverity, NotificationState, QuotaType};
+use crate::projection::{ProjectedUsage, QuotaProjection};
+
+/// When to alert about a quota. Every condition that is set must hold; unset
+/// conditions are ignored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationRule {
+    /// Provider id like "codex"; empty matches every provider
+    pub provider: String,
+    /// Quota id like "session"; empty matches every quota
+    pub quota: String,
+    /// Alert sent when the rule matches, at most once per quota and period
+    pub severity: NotificationSeverity,
+    pub min_current_percent: Option<f32>,
+    pub min_projected_percent: Option<f32>,
+    /// Only while more than this many minutes are left before the reset
+    pub min_remaining_minutes: Option<i64>,
+    /// Only while fewer than this many minutes are left before the reset
+    pub max_remaining_minutes: Option<i64>,
+    /// Only when usage is projected to hit 100% before the reset
+    pub exhaustion: bool,
+}
+
+impl Default for NotificationRule {
+    fn default() -> Self {
+        Self {
+            provider: String::new(),
+            quota: String::new(),
+            severity: NotificationSeverity::Approaching,
+            min_current_percent: None,
+            min_projected_percent: None,
+            min_remaining_minutes: None,
+            max_remaining_minutes: None,
+            exhaustion: false,
+        }
+    }
+}
+
+impl NotificationRule {
+    fn applies_to(&self, provider: &str, quota: &str) -> bool {
+        (self.provider.is_empty() || self.provider == provider)
+            && (self.quota.is_empty() || self.quota == quota)
+    }
+
+    fn matches(&self, p: &ProjectedUsage) -> bool {
+        let remaining_minutes = p.time_remaining_secs / 60;
+        self.min_current_percent
+            .is_none_or(|min| p.current_percent >= min)
+            && self
+                .min_projected_percent
+                .is_none_or(|min| p.projected_percent >= min)
+            && self
+                .min_remaining_minutes
+                .is_none_or(|min| remaining_minutes > min)
+            && self
+                .max_remaining_minutes
+                .is_none_or(|max| remaining_minutes < max)
+            && (!self.exhaustion || p.exhaustion_at.is_some())
+    }
+
+    /// Validate the rule and return errors if invalid
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let percents = [self.min_current_percent, self.min_projected_percent];
+        let minutes = [self.min_remaining_minutes, self.max_remaining_minutes];
+
+        if !matches!(
+            self.severity,
+            NotificationSeverity::Approaching
+                | NotificationSeverity::OverBudget
+                | NotificationSeverity::Exhaustion
+        ) {
+            errors.push("Rule alerts must be approaching, over budget or exhaustion".to_string());
+        }
+        if percents.iter().all(Option::is_none)
+            && minutes.iter().all(Option::is_none)
+            && !self.exhaustion
+        {
+            errors.push("Every notification rule needs at least one condition".to_string());
+        }
+        if percents.iter().flatten().any(|p| *p < 0.0 || *p > 1000.0) {
+            errors.push("Rule percentages must be between 0 and 1000".to_string());
+        }
+        if minutes.iter().flatten().any(|m| *m < 0) {
+            errors.push("Rule time remaining cannot be negative".to_string());
+        }
+
+        errors
+    }
+}
+
+/// Rules equivalent to the former global approaching/over-budget thresholds,
+/// plus the exhaustion alert
+pub fn default_rules(approaching_percent: f32, over_budget_percent: f32) -> Vec<NotificationRule> {
+    vec![
+        NotificationRule {
+            severity: NotificationSeverity::Approaching,
+            min_projected_percent: Some(approaching_percent),
+            ..NotificationRule::default()
+        },
+        NotificationRule {
+            severity: NotificationSeverity::OverBudget,
+            min_projected_percent: Some(over_budget_percent),
+            ..NotificationRule::default()
+        },
+        NotificationRule {
+            severity: NotificationSeverity::Exhaustion,
+            exhaustion: true,
+            ..NotificationRule::default()
+        },
+    ]
+}
+
+/// Evaluate the rules against every quota and return notifications that should
+/// be sent. Over budget supersedes approaching for the same quota.
+pub fn check_rules(
+    projection: &QuotaProjection,
+    rules: &[NotificationRule],
+    state: &NotificationState,
+) -> Vec<NotificationInfo> {
+    let mut notifications = Vec::new();
+
+    for quota in &projection.quotas {
+        let p = &quota.projected;
+        let quota_type = QuotaType::new(&projection.provider_id, &quota.id);
+        let label = format!("{} {}", projection.provider_name, quota.label);
+
+        let mut severities: Vec<NotificationSeverity> = Vec::new();
+        for rule in rules {
+            if rule.applies_to(&projection.provider_id, &quota.id)
+                && rule.matches(p)
+                && !severities.contains(&rule.severity)
+            {
+                severities.push(rule.severity.clone());
+            }
+        }
+        if severities.contains(&NotificationSeverity::OverBudget) {
+            severities.retain(|s| *s != NotificationSeverity::Approaching);
+        }
+        severities.sort_by_key(|s| match s {
+            NotificationSeverity::OverBudget => 0,
+            NotificationSeverity::Approaching => 1,
+            _ => 2,
+        });
+
+        for severity in severities {
+            // The parsed reset instant identifies the period, so it stays the same
+            // on every refresh until the quota resets
+            if state.should_notify(&quota_type, &severity, p.reset_at) {
+                notifications.push(NotificationInfo {
+                    quota_type: quota_type.clone(),
+                    label: label.clone(),
+                    severity,
+                    projected_percent: p.projected_percent,
+                    reset_time: p.reset_at,
+                    exhaustion_at: p.exhaustion_at,
+                    digest: Vec::new(),
+                    detail: None,
+                });
+            }
+        }
+    }
+
+    notifications
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projection::{
+        calculate_projection, parse_reset_time, BudgetStatus, PeriodType, ProjectedQuota,
+        ProjectionMethod,
+    };
+    use crate::provider;
+    use crate::settings::Settings;
+    use chrono::{Duration, Local};
+
+    fn projection(
+        provider: &str,
+        quota: &str,
+        current: f32,
+        projected: f32,
+        remaining_secs: i64,
+    ) -> QuotaProjection {
+        QuotaProjection {
+            provider_id: provider.to_string(),
+            provider_name: provider.to_string(),
+            quotas: vec![ProjectedQuota {
+                id: quota.to_string(),
+                label: quota.to_string(),
+                projected: ProjectedUsage {
+                    current_percent: current,
+                    projected_percent: projected,
+                    status: BudgetStatus::OnTrack,
+                    time_remaining_secs: remaining_secs,
+                    reset_at: Local::now() + Duration::seconds(remaining_secs),
+                    method: ProjectionMethod::Linear,
+                    exhaustion_at: None,
+                },
+            }],
+        }
+    }
+
+    fn severities(notifications: &[NotificationInfo]) -> Vec<NotificationSeverity> {
+        notifications.iter().map(|n| n.severity.clone()).collect()
+    }
+
+    #[test]
+    fn test_per_quota_rules() {
+        let rules = vec![
+            // Codex week: warn at current >= 70%
+            NotificationRule {
+                provider: "codex".to_string(),
+                quota: "week".to_string(),
+                min_current_percent: Some(70.0),
+                ..NotificationRule::default()
+            },
+            // Session: over budget when projected >= 120% with more than an hour left
+            NotificationRule {
+                quota: "session".to_string(),
+                severity: NotificationSeverity::OverBudget,
+                min_projected_percent: Some(120.0),
+                min_remaining_minutes: Some(60),
+                ..NotificationRule::default()
+            },
+        ];
+        // The targets are quotas the providers actually report
+        let providers = provider::registry(&Settings::default());
+        for rule in &rules {
+            assert!(providers
+                .iter()
+                .filter(|p| rule.provider.is_empty() || p.id() == rule.provider)
+                .any(|p| p.quotas().iter().any(|q| q.id == rule.quota)));
+        }
+
+        let state = NotificationState::new();
+        let check = |p: QuotaProjection| severities(&check_rules(&p, &rules, &state));
+
+        assert_eq!(
+            check(projection("codex", "week", 72.0, 90.0, 86400)),
+            vec![NotificationSeverity::Approaching]
+        );
+        assert!(check(projection("codex", "week", 65.0, 140.0, 86400)).is_empty());
+        assert!(check(projection("claude", "week_all_models", 80.0, 90.0, 86400)).is_empty());
+
+        assert_eq!(
+            check(projection("claude", "session", 50.0, 130.0, 2 * 3600)),
+            vec![NotificationSeverity::OverBudget]
+        );
+        assert!(check(projection("claude", "session", 90.0, 130.0, 30 * 60)).is_empty());
+    }
+
+    #[test]
+    fn test_validate_rule() {
+        assert!(default_rules(100.0, 115.0)
+            .iter()
+            .all(|r| r.validate().is_empty()));
+        assert_eq!(NotificationRule::default().validate().len(), 1);
+        let digest = NotificationRule {
+            severity: NotificationSeverity::Digest,
+            min_current_percent: Some(-5.0),
+            ..NotificationRule::default()
+        };
+        assert_eq!(digest.validate().len(), 2);
+    }
+
+    #[test]
+    fn test_notifies_once_per_period_across_refreshes() {
+        let fetch = |percent: f32, reset: &str| QuotaProjection {
+            provider_id: "claude".to_string(),
+            provider_name: "Claude".to_string(),
+            quotas: vec![ProjectedQuota {
+                id: "session".to_string(),
+                label: "Session".to_string(),
+                projected: calculate_projection(
+                    percent,
+                    parse_reset_time(reset).unwrap(),
+                    PeriodType::Session,
+                    85.0,
+                    115.0,
+                ),
+            }],
+        };
+        let rules = default_rules(100.0, 115.0);
+        let mut state = NotificationState::new();
+        let mut refresh = |percent: f32, reset: &str| {
+            let notifications = check_rules(&fetch(percent, reset), &rules, &state);
+            for info in &notifications {
+                state.record_notification(
+                    info.quota_type.clone(),
+                    info.severity.clone(),
+                    info.reset_time,
+                );
+            }
+            notifications.len()
+        };
+
+        // Same period: the reset string parses to the same instant every refresh
+        let reset = (Local::now() + Duration::hours(1))
+            .format("%H:%M")
+            .to_string();
+        // 90% with an hour left: approaching budget and running out before the reset
+        let sent: Vec<usize> = (0..3).map(|i| refresh(90.0 + i as f32, &reset)).collect();
+        assert_eq!(sent, vec![2, 0, 0]);
+
+        // Next period: new reset instant, notify again
+        let next = (Local::now() + Duration::hours(4))
+            .format("%H:%M")
+            .to_string();
+        assert_eq!(refresh(60.0, &next), 2);
+        assert_eq!(refresh(61.0, &next), 0);
+    }
+
+    #[test]
+    fn test_exhaustion_notification() {
+        let projection = QuotaProjection {
+            provider_id: "claude".to_string(),
+            provider_name: "Claude".to_string(),
+            quotas: vec![ProjectedQuota {
+                id: "session".to_string(),
+                label: "Session".to_string(),
+                projected: ProjectedUsage {
+                    current_percent: 90.0,
+                    projected_percent: 150.0,
+                    status: BudgetStatus::OverBudget,
+                    time_remaining_secs: 2 * 3600,
+                    reset_at: Local::now() + Duration::hours(2),
+                    method: ProjectionMethod::Linear,
+                    exhaustion_at: Some(Local::now() + Duration::minutes(20)),
+                },
+            }],
+        };
+
+        let rules = default_rules(100.0, 115.0);
+        let notifications = check_rules(&projection, &rules, &NotificationState::new());
+        assert_eq!(
+            severities(&notifications),
+            vec![
+                NotificationSeverity::OverBudget,
+                NotificationSeverity::Exhaustion
+            ]
+        );
+        assert_eq!(notifications[1].title(), "Claude Session Will Run Out");
+        assert!(notifications[1]
+            .body()
+            .starts_with("You'll be locked out at "));
+    }
+}
@@ -0,0 +1,217 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// How far ahead `WorkSchedule::advance` looks for active time
+const MAX_ADVANCE_DAYS: i64 = 14;
+
+/// Weekly working hours, used to count only active time in weekly projections
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkSchedule {
+    pub enabled: bool,
+    /// Active weekdays, 0 = Monday ... 6 = Sunday
+    pub days: Vec<u8>,
+    /// First active hour of the day (0-23)
+    pub start_hour: u32,
+    /// End of the active hours, exclusive (1-24)
+    pub end_hour: u32,
+    /// IANA timezone like "Europe/Berlin"; empty uses the system timezone
+    pub timezone: String,
+}
+
+impl Default for WorkSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            days: vec![0, 1, 2, 3, 4],
+            start_hour: 9,
+            end_hour: 18,
+            timezone: String::new(),
+        }
+    }
+}
+
+impl WorkSchedule {
+    /// Validate the schedule and return errors if invalid
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.days.iter().any(|d| *d > 6) {
+            errors.push("Working days must be between 0 (Monday) and 6 (Sunday)".to_string());
+        }
+        if self.enabled && self.days.is_empty() {
+            errors.push("Select at least one working day".to_string());
+        }
+        if self.start_hour > 23 || self.end_hour < 1 || self.end_hour > 24 {
+            errors.push("Working hours must be within 0-24".to_string());
+        }
+        if self.start_hour >= self.end_hour {
+            errors.push("Working hours must start before they end".to_string());
+        }
+        if !self.timezone.trim().is_empty() && self.timezone.trim().parse::<Tz>().is_err() {
+            errors.push(format!("Unknown timezone: {}", self.timezone));
+        }
+
+        errors
+    }
+
+    /// Seconds of active (working) time between two instants
+    pub fn active_secs_between(&self, from: DateTime<Local>, to: DateTime<Local>) -> i64 {
+        match self.tz() {
+            Some(tz) => self.active_secs_in(&tz, from, to),
+            None => self.active_secs_in(&Local, from, to),
+        }
+    }
+
+    /// The instant at which `active_secs` of working time will have passed after `from`
+    pub fn advance(&self, from: DateTime<Local>, active_secs: i64) -> Option<DateTime<Local>> {
+        match self.tz() {
+            Some(tz) => self.advance_in(&tz, from, active_secs),
+            None => self.advance_in(&Local, from, active_secs),
+        }
+    }
+
+    fn tz(&self) -> Option<Tz> {
+        self.timezone.trim().parse().ok()
+    }
+
+    /// Active window on a given calendar day in the schedule's timezone
+    fn window_on<Z: TimeZone>(
+        &self,
+        tz: &Z,
+        day: NaiveDate,
+    ) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        let weekday = day.weekday().num_days_from_monday() as u8;
+        if !self.days.contains(&weekday) {
+            return None;
+        }
+
+        let start = tz
+            .from_local_datetime(&day.and_hms_opt(self.start_hour, 0, 0)?)
+            .earliest()?;
+        let end_naive = if self.end_hour >= 24 {
+            day.succ_opt()?.and_hms_opt(0, 0, 0)?
+        } else {
+            day.and_hms_opt(self.end_hour, 0, 0)?
+        };
+        let end = tz.from_local_datetime(&end_naive).earliest()?;
+
+        Some((start.with_timezone(&Local), end.with_timezone(&Local)))
+    }
+
+    fn active_secs_in<Z: TimeZone>(
+        &self,
+        tz: &Z,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+    ) -> i64 {
+        if to <= from {
+            return 0;
+        }
+
+        let last_day = to.with_timezone(tz).date_naive();
+        let mut day = from.with_timezone(tz).date_naive();
+        let mut total = 0;
+        while day <= last_day {
+            if let Some((start, end)) = self.window_on(tz, day) {
+                let (start, end) = (start.max(from), end.min(to));
+                if end > start {
+                    total += end.signed_duration_since(start).num_seconds();
+                }
+            }
+            match day.succ_opt() {
+                Some(next) => day = next,
+                None => break,
+            }
+        }
+        total
+    }
+
+    fn advance_in<Z: TimeZone>(
+        &self,
+        tz: &Z,
+        from: DateTime<Local>,
+        active_secs: i64,
+    ) -> Option<DateTime<Local>> {
+        let mut remaining = active_secs;
+        let mut day = from.with_timezone(tz).date_naive();
+        let last_day = day + Duration::days(MAX_ADVANCE_DAYS);
+        while day <= last_day {
+            if let Some((start, end)) = self.window_on(tz, day) {
+                let start = start.max(from);
+                if end > start {
+                    let available = end.signed_duration_since(start).num_seconds();
+                    if remaining <= available {
+                        return Some(start + Duration::seconds(remaining));
+                    }
+                    remaining -= available;
+                }
+            }
+            day = day.succ_opt()?;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Local> {
+        DateTime::parse_from_rfc3339(s)
+            .unwrap()
+            .with_timezone(&Local)
+    }
+
+    fn office_hours() -> WorkSchedule {
+        WorkSchedule {
+            enabled: true,
+            days: vec![0, 1, 2, 3, 4],
+            start_hour: 9,
+            end_hour: 17,
+            timezone: "UTC".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_active_secs_over_a_week() {
+        let schedule = office_hours();
+        // 2024-01-01 is a Monday
+        let secs =
+            schedule.active_secs_between(utc("2024-01-01T00:00:00Z"), utc("2024-01-08T00:00:00Z"));
+        assert_eq!(secs, 5 * 8 * 3600);
+
+        // Weekend contributes nothing
+        let secs =
+            schedule.active_secs_between(utc("2024-01-06T00:00:00Z"), utc("2024-01-08T00:00:00Z"));
+        assert_eq!(secs, 0);
+
+        // Partial day
+        let secs =
+            schedule.active_secs_between(utc("2024-01-02T15:30:00Z"), utc("2024-01-02T20:00:00Z"));
+        assert_eq!(secs, 90 * 60);
+    }
+
+    #[test]
+    fn test_advance_skips_inactive_time() {
+        let schedule = office_hours();
+        // Friday 16:00 + 2 active hours -> Monday 10:00
+        let at = schedule
+            .advance(utc("2024-01-05T16:00:00Z"), 2 * 3600)
+            .unwrap();
+        assert_eq!(at, utc("2024-01-08T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(WorkSchedule::default().validate().is_empty());
+
+        let invalid = WorkSchedule {
+            start_hour: 18,
+            end_hour: 9,
+            timezone: "Mars/Olympus_Mons".to_string(),
+            ..office_hours()
+        };
+        assert_eq!(invalid.validate().len(), 2);
+    }
+}
@@ -1,20 +1,39 @@
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::path::Path;
 use tauri::AppHandle;
 use tauri_plugin_store::StoreExt;
 
+use crate::hooks::HookCommands;
+use crate::projection::ProjectionMethod;
+use crate::quiet::QuietHours;
+use crate::rules::{default_rules, NotificationRule};
+use crate::schedule::WorkSchedule;
+use crate::tokens::{default_pricing, ModelPrice};
+use crate::usage::ClaudeProfile;
+
 /// Refresh interval options (in minutes)
 pub const REFRESH_INTERVALS: [u64; 4] = [5, 15, 30, 60];
 
 /// Default settings values
 pub mod defaults {
+    use crate::projection::ProjectionMethod;
+
     pub const REFRESH_INTERVAL_MINUTES: u64 = 15;
     pub const THRESHOLD_UNDER_BUDGET: f32 = 85.0;
     pub const THRESHOLD_ON_TRACK: f32 = 115.0;
     pub const NOTIFICATIONS_ENABLED: bool = true;
     pub const NOTIFY_APPROACHING_PERCENT: f32 = 100.0;
     pub const NOTIFY_OVER_BUDGET_PERCENT: f32 = 115.0;
+    pub const NOTIFY_ON_RESET: bool = true;
+    pub const FAILURE_ALERT_AFTER: u32 = 3;
     pub const CODEX_PATH: &str = "codex";
+    pub const GEMINI_ENABLED: bool = false;
+    pub const GEMINI_PATH: &str = "gemini";
+    pub const PROJECTION_METHOD: ProjectionMethod = ProjectionMethod::BurnRate;
+    pub const API_ENABLED: bool = false;
+    pub const API_PORT: u16 = 7423;
+    pub const METRICS_ENABLED: bool = false;
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,9 +42,37 @@ pub struct Settings {
     pub threshold_under_budget: f32,
     pub threshold_on_track: f32,
     pub notifications_enabled: bool,
-    pub notify_approaching_percent: f32,
-    pub notify_over_budget_percent: f32,
+    /// When to alert, per quota; see `rules::check_rules`
+    pub notification_rules: Vec<NotificationRule>,
+    /// Notify when a quota period rolls over and usage is available again
+    pub notify_on_reset: bool,
+    /// Alert after this many failed fetches of a provider in a row; 0 disables
+    pub failure_alert_after: u32,
+    /// When alerts are held back (and optionally sent as a digest afterwards)
+    pub quiet_hours: QuietHours,
+    /// Claude accounts fetched separately; empty fetches the default account
+    pub claude_profiles: Vec<ClaudeProfile>,
     pub codex_path: String,
+    /// Also track the Gemini CLI's daily limits
+    pub gemini_enabled: bool,
+    pub gemini_path: String,
+    /// Preferred projection; burn rate falls back to linear without enough history
+    pub projection_method: ProjectionMethod,
+    /// Working hours for weekly projections; disabled counts every hour
+    pub work_schedule: WorkSchedule,
+    /// Serve the local HTTP API on 127.0.0.1 (applies on restart)
+    pub api_enabled: bool,
+    pub api_port: u16,
+    /// Bearer token required by the API; empty disables auth
+    pub api_token: String,
+    /// Also serve Prometheus metrics at `/metrics` on the API port
+    pub metrics_enabled: bool,
+    /// URLs that receive every alert as a JSON POST
+    pub webhook_urls: Vec<String>,
+    /// Shell commands run on notifications, resets and status changes
+    pub hooks: HookCommands,
+    /// Prices for the cost estimate of transcript token counts
+    pub pricing: Vec<ModelPrice>,
 }
 
 impl Default for Settings {
@@ -35,9 +82,26 @@ impl Default for Settings {
             threshold_under_budget: defaults::THRESHOLD_UNDER_BUDGET,
             threshold_on_track: defaults::THRESHOLD_ON_TRACK,
             notifications_enabled: defaults::NOTIFICATIONS_ENABLED,
-            notify_approaching_percent: defaults::NOTIFY_APPROACHING_PERCENT,
-            notify_over_budget_percent: defaults::NOTIFY_OVER_BUDGET_PERCENT,
+            notification_rules: default_rules(
+                defaults::NOTIFY_APPROACHING_PERCENT,
+                defaults::NOTIFY_OVER_BUDGET_PERCENT,
+            ),
+            notify_on_reset: defaults::NOTIFY_ON_RESET,
+            failure_alert_after: defaults::FAILURE_ALERT_AFTER,
+            quiet_hours: QuietHours::default(),
+            claude_profiles: Vec::new(),
             codex_path: defaults::CODEX_PATH.to_string(),
+            gemini_enabled: defaults::GEMINI_ENABLED,
+            gemini_path: defaults::GEMINI_PATH.to_string(),
+            projection_method: defaults::PROJECTION_METHOD,
+            work_schedule: WorkSchedule::default(),
+            api_enabled: defaults::API_ENABLED,
+            api_port: defaults::API_PORT,
+            api_token: String::new(),
+            metrics_enabled: defaults::METRICS_ENABLED,
+            webhook_urls: Vec::new(),
+            hooks: HookCommands::default(),
+            pricing: default_pricing(),
         }
     }
 }
@@ -66,23 +130,72 @@ impl Settings {
             errors.push("Under budget must be less than on track threshold".to_string());
         }
 
-        if self.notify_approaching_percent < 1.0 || self.notify_approaching_percent > 200.0 {
-            errors.push("Approaching notification must be between 1 and 200".to_string());
+        for (i, rule) in self.notification_rules.iter().enumerate() {
+            errors.extend(
+                rule.validate()
+                    .into_iter()
+                    .map(|e| format!("Rule {}: {}", i + 1, e)),
+            );
         }
 
-        if self.notify_over_budget_percent < 1.0 || self.notify_over_budget_percent > 200.0 {
-            errors.push("Over budget notification must be between 1 and 200".to_string());
+        if self.failure_alert_after > 100 {
+            errors.push("Failure alert must be after 0 to 100 failed fetches".to_string());
         }
 
-        if self.notify_over_budget_percent < self.notify_approaching_percent {
-            errors
-                .push("Over budget notification must be >= approaching notification".to_string());
+        for (i, profile) in self.claude_profiles.iter().enumerate() {
+            errors.extend(
+                profile
+                    .validate()
+                    .into_iter()
+                    .map(|e| format!("Profile {}: {}", i + 1, e)),
+            );
+        }
+        let mut profile_ids: Vec<String> =
+            self.claude_profiles.iter().map(|p| p.provider_id()).collect();
+        profile_ids.sort();
+        profile_ids.dedup();
+        if profile_ids.len() < self.claude_profiles.len() {
+            errors.push("Claude profile names must be unique".to_string());
+        }
+        // Profiles on the same directory would fetch the same account twice
+        let mut profile_dirs: Vec<_> =
+            self.claude_profiles.iter().map(|p| p.projects_dir()).collect();
+        profile_dirs.sort();
+        profile_dirs.dedup();
+        if profile_dirs.len() < self.claude_profiles.len() {
+            errors.push("Claude profiles must use different config directories".to_string());
         }
 
         if self.codex_path.trim().is_empty() {
             errors.push("Codex path cannot be empty".to_string());
         }
 
+        if self.gemini_enabled && self.gemini_path.trim().is_empty() {
+            errors.push("Gemini path cannot be empty".to_string());
+        }
+
+        // Working hours are chosen per quota from the schedule, not as a global method
+        if self.projection_method == ProjectionMethod::WorkHours {
+            errors.push("Projection method must be linear or burn rate".to_string());
+        }
+
+        errors.extend(self.work_schedule.validate());
+        errors.extend(self.quiet_hours.validate());
+
+        if self.api_port < 1024 {
+            errors.push("API port must be between 1024 and 65535".to_string());
+        }
+
+        for url in &self.webhook_urls {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                errors.push(format!("Webhook URL must start with http:// or https://: {}", url));
+            }
+        }
+
+        for price in &self.pricing {
+            errors.extend(price.validate());
+        }
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -101,42 +214,110 @@ pub fn load_settings(app: &AppHandle) -> Settings {
         }
     };
 
+    settings_from(|key| store.get(key))
+}
+
+/// Load settings from a settings.json written by the store, for running without
+/// the Tauri app (headless mode). A missing file yields the defaults.
+pub fn load_settings_file(path: &Path) -> Settings {
+    let values: serde_json::Map<String, Value> = match std::fs::read_to_string(path) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(values) => values,
+            Err(e) => {
+                eprintln!("Failed to parse {}: {}", path.display(), e);
+                return Settings::default();
+            }
+        },
+        Err(_) => return Settings::default(),
+    };
+
+    settings_from(|key| values.get(key).cloned())
+}
+
+/// Build settings from stored values, falling back to defaults per field
+fn settings_from(get: impl Fn(&str) -> Option<Value>) -> Settings {
     // Try to load each field individually, falling back to defaults
     let defaults = Settings::default();
 
     let settings = Settings {
-        refresh_interval_minutes: store
-            .get("refresh_interval_minutes")
+        refresh_interval_minutes: get("refresh_interval_minutes")
             .and_then(|v| v.as_u64())
             .unwrap_or(defaults.refresh_interval_minutes),
-        threshold_under_budget: store
-            .get("threshold_under_budget")
+        threshold_under_budget: get("threshold_under_budget")
             .and_then(|v| v.as_f64())
             .map(|v| v as f32)
             .unwrap_or(defaults.threshold_under_budget),
-        threshold_on_track: store
-            .get("threshold_on_track")
+        threshold_on_track: get("threshold_on_track")
             .and_then(|v| v.as_f64())
             .map(|v| v as f32)
             .unwrap_or(defaults.threshold_on_track),
-        notifications_enabled: store
-            .get("notifications_enabled")
+        notifications_enabled: get("notifications_enabled")
             .and_then(|v| v.as_bool())
             .unwrap_or(defaults.notifications_enabled),
-        notify_approaching_percent: store
-            .get("notify_approaching_percent")
-            .and_then(|v| v.as_f64())
-            .map(|v| v as f32)
-            .unwrap_or(defaults.notify_approaching_percent),
-        notify_over_budget_percent: store
-            .get("notify_over_budget_percent")
-            .and_then(|v| v.as_f64())
-            .map(|v| v as f32)
-            .unwrap_or(defaults.notify_over_budget_percent),
-        codex_path: store
-            .get("codex_path")
+        notification_rules: get("notification_rules")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_else(|| {
+                // Settings saved before rules only had the two global thresholds
+                let percent = |key: &str, default: f32| {
+                    get(key)
+                        .and_then(|v| v.as_f64())
+                        .map_or(default, |v| v as f32)
+                };
+                default_rules(
+                    percent("notify_approaching_percent", defaults::NOTIFY_APPROACHING_PERCENT),
+                    percent("notify_over_budget_percent", defaults::NOTIFY_OVER_BUDGET_PERCENT),
+                )
+            }),
+        notify_on_reset: get("notify_on_reset")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.notify_on_reset),
+        failure_alert_after: get("failure_alert_after")
+            .and_then(|v| v.as_u64())
+            .and_then(|v| u32::try_from(v).ok())
+            .unwrap_or(defaults.failure_alert_after),
+        quiet_hours: get("quiet_hours")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or(defaults.quiet_hours),
+        claude_profiles: get("claude_profiles")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or(defaults.claude_profiles),
+        codex_path: get("codex_path")
             .and_then(|v| v.as_str().map(|s| s.to_string()))
             .unwrap_or_else(|| defaults::CODEX_PATH.to_string()),
+        gemini_enabled: get("gemini_enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.gemini_enabled),
+        gemini_path: get("gemini_path")
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| defaults::GEMINI_PATH.to_string()),
+        projection_method: get("projection_method")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or(defaults.projection_method),
+        work_schedule: get("work_schedule")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or(defaults.work_schedule),
+        api_enabled: get("api_enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.api_enabled),
+        api_port: get("api_port")
+            .and_then(|v| v.as_u64())
+            .and_then(|v| u16::try_from(v).ok())
+            .unwrap_or(defaults.api_port),
+        api_token: get("api_token")
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_default(),
+        metrics_enabled: get("metrics_enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(defaults.metrics_enabled),
+        webhook_urls: get("webhook_urls")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or(defaults.webhook_urls),
+        hooks: get("hooks")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or(defaults.hooks),
+        pricing: get("pricing")
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or(defaults.pricing),
     };
 
     // Validate loaded settings, use defaults if invalid
@@ -166,15 +347,23 @@ pub fn save_settings(app: &AppHandle, settings: &Settings) -> Result<(), String>
     );
     store.set("threshold_on_track", json!(settings.threshold_on_track));
     store.set("notifications_enabled", json!(settings.notifications_enabled));
-    store.set(
-        "notify_approaching_percent",
-        json!(settings.notify_approaching_percent),
-    );
-    store.set(
-        "notify_over_budget_percent",
-        json!(settings.notify_over_budget_percent),
-    );
+    store.set("notification_rules", json!(settings.notification_rules));
+    store.set("notify_on_reset", json!(settings.notify_on_reset));
+    store.set("failure_alert_after", json!(settings.failure_alert_after));
+    store.set("quiet_hours", json!(settings.quiet_hours));
+    store.set("claude_profiles", json!(settings.claude_profiles));
     store.set("codex_path", json!(settings.codex_path));
+    store.set("gemini_enabled", json!(settings.gemini_enabled));
+    store.set("gemini_path", json!(settings.gemini_path));
+    store.set("projection_method", json!(settings.projection_method));
+    store.set("work_schedule", json!(settings.work_schedule));
+    store.set("api_enabled", json!(settings.api_enabled));
+    store.set("api_port", json!(settings.api_port));
+    store.set("api_token", json!(settings.api_token));
+    store.set("metrics_enabled", json!(settings.metrics_enabled));
+    store.set("webhook_urls", json!(settings.webhook_urls));
+    store.set("hooks", json!(settings.hooks));
+    store.set("pricing", json!(settings.pricing));
 
     store.save().map_err(|e| e.to_string())?;
 
@@ -194,24 +383,180 @@ mod tests {
 
     #[test]
     fn test_invalid_refresh_interval() {
-        let mut settings = Settings::default();
-        settings.refresh_interval_minutes = 10; // Invalid
+        let settings = Settings {
+            refresh_interval_minutes: 10, // Invalid
+            ..Settings::default()
+        };
         assert!(settings.validate().is_err());
     }
 
     #[test]
     fn test_invalid_threshold_relationship() {
-        let mut settings = Settings::default();
-        settings.threshold_under_budget = 120.0;
-        settings.threshold_on_track = 100.0;
+        let settings = Settings {
+            threshold_under_budget: 120.0,
+            threshold_on_track: 100.0,
+            ..Settings::default()
+        };
         assert!(settings.validate().is_err());
     }
 
     #[test]
-    fn test_invalid_notification_relationship() {
-        let mut settings = Settings::default();
-        settings.notify_approaching_percent = 120.0;
-        settings.notify_over_budget_percent = 100.0;
+    fn test_invalid_notification_rule() {
+        let settings = Settings {
+            notification_rules: vec![NotificationRule::default()],
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate().unwrap_err(),
+            vec!["Rule 1: Every notification rule needs at least one condition"]
+        );
+    }
+
+    #[test]
+    fn test_legacy_thresholds_become_rules() {
+        let settings = settings_from(|key| match key {
+            "notify_approaching_percent" => Some(json!(90.0)),
+            "notify_over_budget_percent" => Some(json!(130.0)),
+            _ => None,
+        });
+        assert_eq!(settings.notification_rules, default_rules(90.0, 130.0));
+    }
+
+    #[test]
+    fn test_invalid_work_schedule() {
+        let settings = Settings {
+            work_schedule: WorkSchedule {
+                enabled: true,
+                days: Vec::new(),
+                ..WorkSchedule::default()
+            },
+            ..Settings::default()
+        };
         assert!(settings.validate().is_err());
     }
+
+    #[test]
+    fn test_load_settings_file() {
+        let path =
+            std::env::temp_dir().join(format!("notifai-settings-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"refresh_interval_minutes": 5, "codex_path": "/opt/codex"}"#)
+            .unwrap();
+
+        let settings = load_settings_file(&path);
+        assert_eq!(settings.refresh_interval_minutes, 5);
+        assert_eq!(settings.codex_path, "/opt/codex");
+        assert_eq!(settings.threshold_on_track, defaults::THRESHOLD_ON_TRACK);
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            load_settings_file(&path).refresh_interval_minutes,
+            defaults::REFRESH_INTERVAL_MINUTES
+        );
+    }
+
+    #[test]
+    fn test_invalid_webhook_url() {
+        let settings = Settings {
+            webhook_urls: vec!["http://127.0.0.1:9000/hook".to_string(), "ftp://x".to_string()],
+            ..Settings::default()
+        };
+        assert_eq!(settings.validate().unwrap_err().len(), 1);
+    }
+
+    #[test]
+    fn test_gemini_path_required_when_enabled() {
+        let disabled = Settings {
+            gemini_path: " ".to_string(),
+            ..Settings::default()
+        };
+        assert!(disabled.validate().is_ok());
+        let enabled = Settings {
+            gemini_enabled: true,
+            ..disabled
+        };
+        assert_eq!(enabled.validate().unwrap_err(), vec!["Gemini path cannot be empty"]);
+    }
+
+    #[test]
+    fn test_invalid_price() {
+        let mut pricing = default_pricing();
+        pricing[0].output = -1.0;
+        let settings = Settings {
+            pricing,
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate().unwrap_err(),
+            vec!["Prices for claude-opus-4-5 cannot be negative"]
+        );
+    }
+
+    #[test]
+    fn test_claude_profiles_need_unique_names() {
+        let profile = |name: &str, config_dir: &str| ClaudeProfile {
+            name: name.to_string(),
+            config_dir: config_dir.to_string(),
+            ..ClaudeProfile::default()
+        };
+        let settings = Settings {
+            claude_profiles: vec![profile("Work", "~/.claude-work"), profile("Personal", "")],
+            ..Settings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        let settings = Settings {
+            claude_profiles: vec![
+                profile("Work", "/a"),
+                profile("work", "/b"),
+                profile(" ", "/c"),
+            ],
+            ..Settings::default()
+        };
+        assert_eq!(
+            settings.validate().unwrap_err(),
+            vec![
+                "Profile 3: Every Claude profile needs a name",
+                "Claude profile names must be unique",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_claude_profiles_need_distinct_config_dirs() {
+        let profile = |name: &str, config_dir: &str| ClaudeProfile {
+            name: name.to_string(),
+            config_dir: config_dir.to_string(),
+            ..ClaudeProfile::default()
+        };
+        let duplicate = vec!["Claude profiles must use different config directories"];
+
+        // Both on the CLI's default directory
+        let settings = Settings {
+            claude_profiles: vec![profile("Work", ""), profile("Personal", " ")],
+            ..Settings::default()
+        };
+        assert_eq!(settings.validate().unwrap_err(), duplicate);
+
+        // The same directory before and after expanding `~`
+        let home = dirs::home_dir().unwrap();
+        let settings = Settings {
+            claude_profiles: vec![
+                profile("Work", "~/.claude-work"),
+                profile("Personal", &home.join(".claude-work").to_string_lossy()),
+            ],
+            ..Settings::default()
+        };
+        assert_eq!(settings.validate().unwrap_err(), duplicate);
+
+        // A `CLAUDE_CONFIG_DIR` in the environment is the directory used
+        let mut personal = profile("Personal", "~/.claude-personal");
+        personal
+            .env
+            .insert("CLAUDE_CONFIG_DIR".to_string(), "~/.claude-work".to_string());
+        let settings = Settings {
+            claude_profiles: vec![profile("Work", "~/.claude-work"), personal],
+            ..Settings::default()
+        };
+        assert_eq!(settings.validate().unwrap_err(), duplicate);
+    }
 }
@@ -0,0 +1,72 @@
+use vt100::Parser;
+
+/// In-memory VT100 screen fed with raw PTY output.
+///
+/// Escape sequences (cursor movement, line/screen erase, alternate screen) are
+/// interpreted, so `contents()` returns what a real terminal would display instead
+/// of every byte the CLI ever wrote.
+pub struct VirtualTerminal {
+    parser: Parser,
+}
+
+impl VirtualTerminal {
+    pub fn new(rows: u16, cols: u16) -> Self {
+        Self {
+            parser: Parser::new(rows, cols, 0),
+        }
+    }
+
+    /// Feed raw bytes read from the PTY
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.parser.process(bytes);
+    }
+
+    /// Currently rendered screen as plain text, one line per row
+    pub fn contents(&self) -> String {
+        self.parser.screen().contents()
+    }
+
+    /// Response to a cursor position query (`ESC[6n`), e.g. `ESC[5;1R`
+    pub fn cursor_position_report(&self) -> Vec<u8> {
+        let (row, col) = self.parser.screen().cursor_position();
+        format!("\x1b[{};{}R", row + 1, col + 1).into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_updates_replace_stale_content() {
+        let mut term = VirtualTerminal::new(24, 80);
+        term.feed(b"Loading usage data...\r\n");
+        // Move up one line, erase it and draw the real content in place
+        term.feed(b"\x1b[1A\x1b[2KCurrent session\r\n17% used\r\n");
+
+        let contents = term.contents();
+        assert!(!contents.contains("Loading usage data"));
+        assert!(contents.contains("Current session"));
+        assert!(contents.contains("17% used"));
+    }
+
+    #[test]
+    fn test_clear_screen_and_alternate_screen() {
+        let mut term = VirtualTerminal::new(24, 80);
+        term.feed(b"old frame\x1b[2J\x1b[Hnew frame");
+        assert_eq!(term.contents().trim(), "new frame");
+
+        // Alternate screen content disappears once the app switches back
+        term.feed(b"\x1b[?1049h\x1b[Hpopup");
+        assert!(term.contents().contains("popup"));
+        term.feed(b"\x1b[?1049l");
+        assert_eq!(term.contents().trim(), "new frame");
+    }
+
+    #[test]
+    fn test_cursor_position_report() {
+        let mut term = VirtualTerminal::new(24, 80);
+        term.feed(b"line 1\r\nab");
+        assert_eq!(term.cursor_position_report(), b"\x1b[2;3R".to_vec());
+    }
+}
@@ -0,0 +1,357 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Token counts of one or more model responses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TokenCounts {
+    pub input: u64,
+    pub output: u64,
+    /// Input tokens written to the prompt cache
+    pub cache_write: u64,
+    /// Input tokens read from the prompt cache
+    pub cache_read: u64,
+}
+
+impl TokenCounts {
+    pub fn total(&self) -> u64 {
+        self.input + self.output + self.cache_write + self.cache_read
+    }
+
+    pub fn add(&mut self, other: &TokenCounts) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_write += other.cache_write;
+        self.cache_read += other.cache_read;
+    }
+}
+
+/// API list prices of a model family in USD per million tokens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Model name prefix, e.g. "claude-sonnet-4"; the longest matching prefix wins
+    pub model: String,
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    fn new(model: &str, input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            model: model.to_string(),
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    /// Cost of `counts` in USD
+    pub fn cost(&self, counts: &TokenCounts) -> f64 {
+        (counts.input as f64 * self.input
+            + counts.output as f64 * self.output
+            + counts.cache_write as f64 * self.cache_write
+            + counts.cache_read as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+
+    /// Validate the price and return errors if invalid
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.model.trim().is_empty() {
+            errors.push("Every price needs a model name".to_string());
+        }
+        let prices = [self.input, self.output, self.cache_write, self.cache_read];
+        if prices.iter().any(|p| !p.is_finite() || *p < 0.0) {
+            errors.push(format!("Prices for {} cannot be negative", self.model));
+        }
+        errors
+    }
+}
+
+/// List prices at the time of writing; editable in settings
+pub fn default_pricing() -> Vec<ModelPrice> {
+    vec![
+        ModelPrice::new("claude-opus-4-5", 5.0, 25.0, 6.25, 0.5),
+        ModelPrice::new("claude-opus-4", 15.0, 75.0, 18.75, 1.5),
+        ModelPrice::new("claude-sonnet-4", 3.0, 15.0, 3.75, 0.3),
+        ModelPrice::new("claude-3-7-sonnet", 3.0, 15.0, 3.75, 0.3),
+        ModelPrice::new("claude-haiku-4-5", 1.0, 5.0, 1.25, 0.1),
+        ModelPrice::new("claude-3-5-haiku", 0.8, 4.0, 1.0, 0.08),
+        ModelPrice::new("gpt-5", 1.25, 10.0, 0.0, 0.125),
+        ModelPrice::new("gpt-5-mini", 0.25, 2.0, 0.0, 0.025),
+    ]
+}
+
+/// Price for a model name, by longest matching prefix
+pub fn price_for<'a>(pricing: &'a [ModelPrice], model: &str) -> Option<&'a ModelPrice> {
+    pricing
+        .iter()
+        .filter(|p| !p.model.is_empty() && model.starts_with(&p.model))
+        .max_by_key(|p| p.model.len())
+}
+
+/// Tokens used by one model response
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenEntry {
+    pub at: DateTime<Local>,
+    /// Repository or directory the session ran in, e.g. "notifai"
+    pub project: String,
+    pub model: String,
+    pub counts: TokenCounts,
+}
+
+/// Token totals for one day, project and model
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenRow {
+    pub date: NaiveDate,
+    pub project: String,
+    pub model: String,
+    pub tokens: TokenCounts,
+    /// None if the model has no price
+    pub cost_usd: Option<f64>,
+}
+
+/// Days of token data kept: a weekly quota period plus a day of slack
+pub const RETENTION_DAYS: i64 = 8;
+
+/// Oldest instant whose tokens are still kept at `now`
+pub fn retention_cutoff(now: DateTime<Local>) -> DateTime<Local> {
+    now - Duration::days(RETENTION_DAYS)
+}
+
+/// Token totals per local day, project and model of the last `RETENTION_DAYS`
+#[derive(Debug, Clone, Default)]
+pub struct TokenLedger {
+    totals: HashMap<(NaiveDate, String, String), TokenCounts>,
+    /// Entries of the retention window, in the order they were recorded, for
+    /// attributing quota usage
+    recent: Vec<TokenEntry>,
+}
+
+impl TokenLedger {
+    /// Add an entry; entries older than the retention window are ignored
+    pub fn record(&mut self, entry: &TokenEntry) {
+        if entry.at <= retention_cutoff(Local::now()) {
+            return;
+        }
+        let key = (
+            entry.at.date_naive(),
+            entry.project.clone(),
+            entry.model.clone(),
+        );
+        self.totals.entry(key).or_default().add(&entry.counts);
+        self.recent.push(entry.clone());
+    }
+
+    /// Drop what fell out of the retention window
+    pub fn prune(&mut self, now: DateTime<Local>) {
+        let cutoff = retention_cutoff(now);
+        self.recent.retain(|e| e.at > cutoff);
+        self.totals.retain(|(day, _, _), _| *day >= cutoff.date_naive());
+    }
+
+    /// Entries with `from < at <= to`
+    pub fn entries_between(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+    ) -> impl Iterator<Item = &TokenEntry> {
+        self.recent.iter().filter(move |e| e.at > from && e.at <= to)
+    }
+
+    /// Totals of `date` across projects and models, with the estimated cost of
+    /// the priced models
+    pub fn day_total(&self, date: NaiveDate, pricing: &[ModelPrice]) -> (TokenCounts, f64) {
+        let mut counts = TokenCounts::default();
+        let mut cost = 0.0;
+        for ((day, _, model), c) in &self.totals {
+            if *day == date {
+                counts.add(c);
+                cost += price_for(pricing, model).map_or(0.0, |p| p.cost(c));
+            }
+        }
+        (counts, cost)
+    }
+
+    /// Rows from `since` on, newest day first, then by project and model
+    pub fn rows(&self, since: NaiveDate, pricing: &[ModelPrice]) -> Vec<TokenRow> {
+        let mut rows: Vec<TokenRow> = self
+            .totals
+            .iter()
+            .filter(|((day, _, _), _)| *day >= since)
+            .map(|((date, project, model), tokens)| TokenRow {
+                date: *date,
+                project: project.clone(),
+                model: model.clone(),
+                tokens: *tokens,
+                cost_usd: price_for(pricing, model).map(|p| p.cost(tokens)),
+            })
+            .collect();
+        rows.sort_by(|a, b| (b.date, &a.project, &a.model).cmp(&(a.date, &b.project, &b.model)));
+        rows
+    }
+
+    /// Menu line for today, e.g. "Today: 2.1M tokens, ~$14 equivalent"
+    pub fn today_line(&self, now: DateTime<Local>, pricing: &[ModelPrice]) -> Option<String> {
+        let (counts, cost) = self.day_total(now.date_naive(), pricing);
+        if counts.total() == 0 {
+            return None;
+        }
+        Some(format!(
+            "Today: {} tokens, ~${} equivalent",
+            format_tokens(counts.total()),
+            format_cost(cost)
+        ))
+    }
+
+    /// Menu line with today's busiest projects, e.g. "notifai 1.2M, api 310k"
+    pub fn projects_line(&self, now: DateTime<Local>) -> Option<String> {
+        let mut projects: HashMap<&str, u64> = HashMap::new();
+        for ((day, project, _), counts) in &self.totals {
+            if *day == now.date_naive() {
+                *projects.entry(project).or_default() += counts.total();
+            }
+        }
+        let mut projects: Vec<(&str, u64)> = projects.into_iter().collect();
+        projects.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let parts: Vec<String> = projects
+            .iter()
+            .take(MENU_PROJECTS)
+            .map(|(project, total)| format!("{} {}", project, format_tokens(*total)))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// Projects shown in the menu line, the rest are left out
+const MENU_PROJECTS: usize = 3;
+
+/// Compact token count, e.g. "950", "12k", "2.1M"
+pub fn format_tokens(n: u64) -> String {
+    if n >= 1_000_000 {
+        format!("{:.1}M", n as f64 / 1_000_000.0)
+    } else if n >= 1_000 {
+        format!("{}k", n / 1_000)
+    } else {
+        n.to_string()
+    }
+}
+
+/// Dollars with cents below $10, whole dollars above
+fn format_cost(cost: f64) -> String {
+    if cost < 10.0 {
+        format!("{:.2}", cost)
+    } else {
+        format!("{:.0}", cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        at: DateTime<Local>,
+        project: &str,
+        model: &str,
+        input: u64,
+        output: u64,
+    ) -> TokenEntry {
+        TokenEntry {
+            at,
+            project: project.to_string(),
+            model: model.to_string(),
+            counts: TokenCounts {
+                input,
+                output,
+                cache_write: 0,
+                cache_read: 1_000_000,
+            },
+        }
+    }
+
+    #[test]
+    fn test_price_for_longest_prefix() {
+        let pricing = default_pricing();
+        assert_eq!(
+            price_for(&pricing, "claude-opus-4-5-20251101")
+                .unwrap()
+                .input,
+            5.0
+        );
+        assert_eq!(
+            price_for(&pricing, "claude-opus-4-1-20250805")
+                .unwrap()
+                .input,
+            15.0
+        );
+        assert_eq!(price_for(&pricing, "gpt-5-codex").unwrap().model, "gpt-5");
+        assert!(price_for(&pricing, "o3").is_none());
+        assert!(pricing.iter().all(|p| p.validate().is_empty()));
+    }
+
+    #[test]
+    fn test_ledger_totals_and_rows() {
+        let now = Local::now();
+        let mut ledger = TokenLedger::default();
+        ledger.record(&entry(
+            now,
+            "repo-a",
+            "claude-sonnet-4-5-20250929",
+            1_000,
+            100_000,
+        ));
+        ledger.record(&entry(
+            now,
+            "repo-a",
+            "claude-sonnet-4-5-20250929",
+            1_000,
+            100_000,
+        ));
+        ledger.record(&entry(now, "repo-b", "some-local-model", 500, 500));
+        ledger.record(&entry(
+            now - Duration::days(2),
+            "repo-a",
+            "claude-opus-4-1",
+            10,
+            10,
+        ));
+
+        let pricing = default_pricing();
+        let (counts, cost) = ledger.day_total(now.date_naive(), &pricing);
+        assert_eq!(counts.input, 2_500);
+        assert_eq!(counts.total(), 3_203_000);
+        // 2k input at $3, 200k output at $15, 2M cache reads at $0.30; the
+        // unpriced model adds nothing
+        assert!((cost - (0.006 + 3.0 + 0.6)).abs() < 1e-9);
+        assert_eq!(
+            ledger.today_line(now, &pricing).as_deref(),
+            Some("Today: 3.2M tokens, ~$3.61 equivalent")
+        );
+        assert_eq!(
+            ledger.projects_line(now).as_deref(),
+            Some("repo-a 2.2M, repo-b 1.0M")
+        );
+
+        let rows = ledger.rows(now.date_naive() - Duration::days(1), &pricing);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].project, "repo-a");
+        assert_eq!(rows[1].cost_usd, None);
+        assert_eq!(
+            ledger
+                .rows(now.date_naive() - Duration::days(7), &pricing)
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(12_400), "12k");
+        assert_eq!(format_tokens(2_140_000), "2.1M");
+    }
+}
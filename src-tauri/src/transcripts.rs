@@ -0,0 +1,498 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::tokens::{retention_cutoff, TokenCounts, TokenEntry, TokenLedger};
+
+/// Turns transcript lines of one CLI into token entries
+pub trait TranscriptFormat: Send {
+    /// Parse a line of `file`. Returns a key identifying the model response,
+    /// so responses logged more than once are counted once, and its tokens.
+    fn parse_line(&mut self, file: &Path, line: &str) -> Option<(String, TokenEntry)>;
+
+    /// Drop any state kept for `file`, which is no longer read
+    fn forget(&mut self, _file: &Path) {}
+
+    /// Copy of the format and the state it kept
+    fn clone_box(&self) -> Box<dyn TranscriptFormat>;
+}
+
+impl Clone for Box<dyn TranscriptFormat> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Incrementally scans the JSONL transcripts under a directory into a ledger
+#[derive(Clone)]
+pub struct TranscriptScanner {
+    root: PathBuf,
+    format: Box<dyn TranscriptFormat>,
+    /// Bytes of each file already read, up to the last complete line
+    offsets: HashMap<PathBuf, u64>,
+    /// Keys of the responses counted, with their time, for the retention window
+    seen: HashMap<String, DateTime<Local>>,
+    pub ledger: TokenLedger,
+}
+
+impl TranscriptScanner {
+    pub fn new(root: PathBuf, format: Box<dyn TranscriptFormat>) -> Self {
+        Self {
+            root,
+            format,
+            offsets: HashMap::new(),
+            seen: HashMap::new(),
+            ledger: TokenLedger::default(),
+        }
+    }
+
+    /// Directory the transcripts are read from
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Read lines appended since the last scan. A missing root is not an error,
+    /// the CLI may just not be installed. Files last written before the
+    /// retention window are skipped, so the first scan doesn't read years of
+    /// transcripts.
+    pub fn scan(&mut self) -> Result<()> {
+        if !self.root.is_dir() {
+            return Ok(());
+        }
+        let cutoff = retention_cutoff(Local::now());
+        let mut files = Vec::new();
+        collect_jsonl(&self.root, &mut files)?;
+        files.retain(|path| {
+            std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .map_or(true, |modified| DateTime::<Local>::from(modified) > cutoff)
+        });
+
+        // Files that went away or out of the window start over if written again
+        let gone: Vec<PathBuf> = self
+            .offsets
+            .keys()
+            .filter(|path| !files.contains(path))
+            .cloned()
+            .collect();
+        for path in gone {
+            self.offsets.remove(&path);
+            self.format.forget(&path);
+        }
+
+        for path in files {
+            let offset = self.offsets.get(&path).copied().unwrap_or(0);
+            let (lines, read_to) = match read_new_lines(&path, offset) {
+                Ok(read) => read,
+                Err(e) => {
+                    eprintln!("[NotifAI] Failed to read {}: {:#}", path.display(), e);
+                    continue;
+                }
+            };
+            for line in lines.lines() {
+                let Some((key, entry)) = self.format.parse_line(&path, line) else {
+                    continue;
+                };
+                if entry.at > cutoff && self.seen.insert(key, entry.at).is_none() {
+                    self.ledger.record(&entry);
+                }
+            }
+            self.offsets.insert(path, read_to);
+        }
+        self.seen.retain(|_, at| *at > cutoff);
+        self.ledger.prune(Local::now());
+        Ok(())
+    }
+}
+
+/// Every `.jsonl` file below `dir`
+fn collect_jsonl(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("Failed to list {}", dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_jsonl(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "jsonl") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Complete lines written after `offset`, and the offset after the last of them.
+/// A file that shrank was rewritten and is read again from the start.
+fn read_new_lines(path: &Path, offset: u64) -> Result<(String, u64)> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let offset = if len < offset { 0 } else { offset };
+    if len == offset {
+        return Ok((String::new(), offset));
+    }
+
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::new();
+    file.take(len - offset).read_to_end(&mut bytes)?;
+    // Leave a line that is still being written for the next scan
+    let complete = bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    bytes.truncate(complete);
+    Ok((
+        String::from_utf8_lossy(&bytes).into_owned(),
+        offset + complete as u64,
+    ))
+}
+
+/// Transcript directory of Claude Code, `$CLAUDE_CONFIG_DIR/projects` or
+/// `~/.claude/projects`
+pub fn claude_projects_dir() -> Option<PathBuf> {
+    match std::env::var_os("CLAUDE_CONFIG_DIR") {
+        Some(dir) => Some(PathBuf::from(dir).join("projects")),
+        None => dirs::home_dir().map(|home| home.join(".claude").join("projects")),
+    }
+}
+
+/// Rollout directory of Codex, `$CODEX_HOME/sessions` or `~/.codex/sessions`
+pub fn codex_sessions_dir() -> Option<PathBuf> {
+    match std::env::var_os("CODEX_HOME") {
+        Some(dir) => Some(PathBuf::from(dir).join("sessions")),
+        None => dirs::home_dir().map(|home| home.join(".codex").join("sessions")),
+    }
+}
+
+/// Last component of a path, e.g. "notifai" for "/home/me/src/notifai"
+pub fn project_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Claude Code transcripts: one JSON object per line, assistant messages carry
+/// the model and token usage. Streamed responses are logged once per content
+/// block with the same message id and usage.
+#[derive(Clone)]
+pub struct ClaudeFormat;
+
+impl TranscriptFormat for ClaudeFormat {
+    fn clone_box(&self) -> Box<dyn TranscriptFormat> {
+        Box::new(self.clone())
+    }
+
+    fn parse_line(&mut self, file: &Path, line: &str) -> Option<(String, TokenEntry)> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        if value["type"] != "assistant" {
+            return None;
+        }
+        let message = &value["message"];
+        let model = message["model"].as_str()?;
+        // Placeholder messages the CLI writes itself, e.g. after an API error
+        if model.starts_with('<') {
+            return None;
+        }
+
+        let usage = &message["usage"];
+        let tokens = |key: &str| usage[key].as_u64().unwrap_or(0);
+        let counts = TokenCounts {
+            input: tokens("input_tokens"),
+            output: tokens("output_tokens"),
+            cache_write: tokens("cache_creation_input_tokens"),
+            cache_read: tokens("cache_read_input_tokens"),
+        };
+        if counts.total() == 0 {
+            return None;
+        }
+
+        let at = DateTime::parse_from_rfc3339(value["timestamp"].as_str()?)
+            .ok()?
+            .with_timezone(&Local);
+        // The session's working directory, or the transcript directory it's filed
+        // under (the path with separators replaced, e.g. "-home-me-src-notifai")
+        let project = match value["cwd"].as_str() {
+            Some(cwd) => project_name(cwd),
+            None => file
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+        };
+        let key = match (message["id"].as_str(), value["requestId"].as_str()) {
+            (Some(id), Some(request)) => format!("{}:{}", id, request),
+            (Some(id), None) => id.to_string(),
+            _ => value["uuid"].as_str()?.to_string(),
+        };
+
+        Some((
+            key,
+            TokenEntry {
+                at,
+                project,
+                model: model.to_string(),
+                counts,
+            },
+        ))
+    }
+}
+
+/// What a Codex rollout said so far about its session
+#[derive(Clone, Default)]
+struct CodexSession {
+    project: String,
+    model: String,
+    /// Cumulative usage at the last `token_count` event
+    total: TokenCounts,
+}
+
+/// Codex rollouts: a `session_meta` line with the working directory, then
+/// `turn_context` lines with the model and `token_count` events carrying the
+/// session's cumulative usage. Tokens are attributed per event as the growth of
+/// that total, so repeated events add nothing.
+#[derive(Clone, Default)]
+pub struct CodexFormat {
+    sessions: HashMap<PathBuf, CodexSession>,
+}
+
+impl TranscriptFormat for CodexFormat {
+    fn clone_box(&self) -> Box<dyn TranscriptFormat> {
+        Box::new(self.clone())
+    }
+
+    fn forget(&mut self, file: &Path) {
+        self.sessions.remove(file);
+    }
+
+    fn parse_line(&mut self, file: &Path, line: &str) -> Option<(String, TokenEntry)> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        let payload = &value["payload"];
+        let session = self.sessions.entry(file.to_path_buf()).or_default();
+
+        match value["type"].as_str()? {
+            "session_meta" => {
+                *session = CodexSession::default();
+                session.project = payload["cwd"].as_str().map(project_name)?;
+                return None;
+            }
+            "turn_context" => {
+                if let Some(cwd) = payload["cwd"].as_str() {
+                    session.project = project_name(cwd);
+                }
+                session.model = payload["model"].as_str()?.to_string();
+                return None;
+            }
+            "event_msg" if payload["type"] == "token_count" => {}
+            _ => return None,
+        }
+
+        // `info` is null until the first response of the session
+        let usage = &payload["info"]["total_token_usage"];
+        let tokens = |key: &str| usage[key].as_u64().unwrap_or(0);
+        // Cached input is part of the input count
+        let cached = tokens("cached_input_tokens");
+        let total = TokenCounts {
+            input: tokens("input_tokens").saturating_sub(cached),
+            output: tokens("output_tokens"),
+            cache_write: 0,
+            cache_read: cached,
+        };
+        if total.total() <= session.total.total() {
+            return None;
+        }
+        let counts = TokenCounts {
+            input: total.input.saturating_sub(session.total.input),
+            output: total.output.saturating_sub(session.total.output),
+            cache_write: 0,
+            cache_read: total.cache_read.saturating_sub(session.total.cache_read),
+        };
+        session.total = total;
+
+        let at = DateTime::parse_from_rfc3339(value["timestamp"].as_str()?)
+            .ok()?
+            .with_timezone(&Local);
+        let key = format!("{}:{}", file.display(), total.total());
+        Some((
+            key,
+            TokenEntry {
+                at,
+                project: session.project.clone(),
+                model: session.model.clone(),
+                counts,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokens::default_pricing;
+    use std::io::Write;
+
+    fn assistant_line(id: &str, output: u64) -> String {
+        assistant_line_at(id, output, Local::now())
+    }
+
+    fn assistant_line_at(id: &str, output: u64, at: DateTime<Local>) -> String {
+        serde_json::json!({
+            "type": "assistant",
+            "cwd": "/home/me/src/notifai",
+            "requestId": "req_1",
+            "uuid": format!("uuid-{}", id),
+            "timestamp": at.to_rfc3339(),
+            "message": {
+                "id": id,
+                "model": "claude-sonnet-4-5-20250929",
+                "usage": {
+                    "input_tokens": 10,
+                    "output_tokens": output,
+                    "cache_creation_input_tokens": 200,
+                    "cache_read_input_tokens": 3000
+                }
+            }
+        })
+        .to_string()
+    }
+
+    fn today_total(scanner: &TranscriptScanner) -> u64 {
+        let (counts, _) = scanner
+            .ledger
+            .day_total(Local::now().date_naive(), &default_pricing());
+        counts.total()
+    }
+
+    #[test]
+    fn test_parse_claude_line() {
+        let path = Path::new("/tmp/projects/-home-me-src-notifai/session.jsonl");
+        let (key, entry) = ClaudeFormat
+            .parse_line(path, &assistant_line("msg_1", 50))
+            .unwrap();
+        assert_eq!(key, "msg_1:req_1");
+        assert_eq!(entry.project, "notifai");
+        assert_eq!(entry.counts.total(), 3260);
+
+        let user = r#"{"type":"user","message":{"role":"user","content":"hi"}}"#;
+        assert!(ClaudeFormat.parse_line(path, user).is_none());
+    }
+
+    #[test]
+    fn test_scan_is_incremental() {
+        let root = std::env::temp_dir().join(format!("notifai-transcripts-{}", std::process::id()));
+        let dir = root.join("-home-me-src-notifai");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session.jsonl");
+        let mut file = File::create(&path).unwrap();
+        // The same streamed message twice, then half a line still being written
+        let first = assistant_line("msg_1", 50);
+        write!(file, "{}\n{}\n{}", first, first, &first[..20]).unwrap();
+
+        let mut scanner = TranscriptScanner::new(root.clone(), Box::new(ClaudeFormat));
+        scanner.scan().unwrap();
+        assert_eq!(today_total(&scanner), 3260);
+
+        // Finish the partial line (a duplicate) and add a new message
+        writeln!(file, "{}", &first[20..]).unwrap();
+        writeln!(file, "{}", assistant_line("msg_2", 100)).unwrap();
+        scanner.scan().unwrap();
+        assert_eq!(today_total(&scanner), 3260 + 3310);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_scan_keeps_only_the_retention_window() {
+        let root = std::env::temp_dir().join(format!("notifai-retention-{}", std::process::id()));
+        let dir = root.join("-home-me-src-notifai");
+        std::fs::create_dir_all(&dir).unwrap();
+        let old = Local::now() - chrono::Duration::days(30);
+
+        // A transcript nobody wrote to in a month isn't read at all
+        let stale = File::create(dir.join("stale.jsonl")).unwrap();
+        writeln!(&stale, "{}", assistant_line("msg_stale", 50)).unwrap();
+        stale
+            .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(30 * 86400))
+            .unwrap();
+        // A resumed session: only the recent message counts
+        let mut resumed = File::create(dir.join("resumed.jsonl")).unwrap();
+        writeln!(resumed, "{}", assistant_line_at("msg_old", 50, old)).unwrap();
+        writeln!(resumed, "{}", assistant_line("msg_new", 100)).unwrap();
+
+        let mut scanner = TranscriptScanner::new(root.clone(), Box::new(ClaudeFormat));
+        scanner.scan().unwrap();
+        assert_eq!(today_total(&scanner), 3310);
+        assert_eq!(scanner.seen.len(), 1);
+        assert_eq!(scanner.offsets.len(), 1);
+        let (old_counts, _) = scanner.ledger.day_total(old.date_naive(), &default_pricing());
+        assert_eq!(old_counts.total(), 0);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_codex_rollout() {
+        let at = Local::now().to_rfc3339();
+        let token_count = |input: u64, cached: u64, output: u64| {
+            serde_json::json!({
+                "timestamp": at,
+                "type": "event_msg",
+                "payload": {
+                    "type": "token_count",
+                    "info": {
+                        "total_token_usage": {
+                            "input_tokens": input,
+                            "cached_input_tokens": cached,
+                            "output_tokens": output,
+                            "total_tokens": input + output
+                        }
+                    }
+                }
+            })
+            .to_string()
+        };
+        let lines = [
+            serde_json::json!({
+                "timestamp": at,
+                "type": "session_meta",
+                "payload": { "id": "s1", "cwd": "/home/me/src/notifai" }
+            })
+            .to_string(),
+            serde_json::json!({
+                "timestamp": at,
+                "type": "turn_context",
+                "payload": { "cwd": "/home/me/src/notifai", "model": "gpt-5-codex" }
+            })
+            .to_string(),
+            serde_json::json!({
+                "timestamp": at,
+                "type": "event_msg",
+                "payload": { "type": "token_count", "info": null }
+            })
+            .to_string(),
+            token_count(1000, 200, 50),
+            // Repeated when only the rate limits changed
+            token_count(1000, 200, 50),
+            token_count(2500, 1200, 80),
+        ];
+
+        let path = Path::new("/tmp/sessions/2025/01/01/rollout-s1.jsonl");
+        let mut format = CodexFormat::default();
+        let entries: Vec<TokenEntry> = lines
+            .iter()
+            .filter_map(|line| format.parse_line(path, line))
+            .map(|(_, entry)| entry)
+            .collect();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].project, "notifai");
+        assert_eq!(entries[0].model, "gpt-5-codex");
+        assert_eq!(
+            entries[1].counts,
+            TokenCounts {
+                input: 500,
+                output: 30,
+                cache_write: 0,
+                cache_read: 1000,
+            }
+        );
+    }
+}
@@ -1,10 +1,17 @@
 use anyhow::{Context, Result};
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::io::Read;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+use crate::projection::PeriodType;
+use crate::provider::{FetchError, QuotaSpec, UsageProvider};
+use crate::terminal::VirtualTerminal;
+use crate::transcripts::{claude_projects_dir, ClaudeFormat, TranscriptScanner};
+
 /// Debug log for usage fetching - writes to stderr which shows in dev console
 /// Only logs if NOTIFAI_DEBUG env var is set to "1" or "true"
 macro_rules! debug_log {
@@ -15,48 +22,70 @@ macro_rules! debug_log {
     };
 }
 
-/// Parsed usage data from Claude Code /usage command
+/// A single quota reading, normalized to percent used
+#[derive(Debug, Clone, Serialize)]
+pub struct Quota {
+    /// Stable identifier within the provider, e.g. "session" or "week_all_models"
+    pub id: String,
+    /// Display label, e.g. "Week (all models)"
+    pub label: String,
+    pub used_percent: f32,
+    /// Raw reset string as printed by the CLI, e.g. "Dec 22 at 3:59pm (America/Sao_Paulo)"
+    pub reset: Option<String>,
+    pub period: PeriodType,
+}
+
+/// Parsed usage data reported by a provider
 #[derive(Debug, Clone, Serialize)]
 pub struct UsageData {
-    pub current_session_percent: Option<f32>,
-    pub current_session_reset: Option<String>,
-    pub current_week_all_models_percent: Option<f32>,
-    pub current_week_all_models_reset: Option<String>,
-    pub current_week_sonnet_percent: Option<f32>,
-    pub current_week_sonnet_reset: Option<String>,
-    pub extra_usage_enabled: bool,
-    // Codex (OpenAI) limits
-    pub codex_five_hour_left: Option<f32>,
-    pub codex_five_hour_reset: Option<String>,
-    pub codex_week_left: Option<f32>,
-    pub codex_week_reset: Option<String>,
+    pub quotas: Vec<Quota>,
+    /// Claude "Extra usage" status (None if the provider doesn't report it)
+    pub extra_usage_enabled: Option<bool>,
 }
 
 impl UsageData {
     pub fn new() -> Self {
         Self {
-            current_session_percent: None,
-            current_session_reset: None,
-            current_week_all_models_percent: None,
-            current_week_all_models_reset: None,
-            current_week_sonnet_percent: None,
-            current_week_sonnet_reset: None,
-            extra_usage_enabled: false,
-            codex_five_hour_left: None,
-            codex_five_hour_reset: None,
-            codex_week_left: None,
-            codex_week_reset: None,
+            quotas: Vec::new(),
+            extra_usage_enabled: None,
         }
     }
 }
 
-fn parse_usage_output(raw_output: &str) -> Result<UsageData> {
-    debug_log!("Parsing output ({} bytes raw)", raw_output.len());
+/// Turn a section name like "all models" into an id fragment like "all_models"
+pub fn slugify(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Detect a quota section header like "Current session" or "Current week (Opus only)".
+/// Returns (id, label, period).
+fn parse_section_header(line: &str, week_re: &Regex) -> Option<(String, String, PeriodType)> {
+    if line.to_lowercase().contains("current session") {
+        return Some((
+            "session".to_string(),
+            "Session".to_string(),
+            PeriodType::Session,
+        ));
+    }
+
+    let caps = week_re.captures(line)?;
+    match caps.get(1).map(|m| m.as_str().trim()) {
+        Some(name) if !name.is_empty() => Some((
+            format!("week_{}", slugify(name)),
+            format!("Week ({})", name),
+            PeriodType::Weekly,
+        )),
+        _ => Some(("week".to_string(), "Week".to_string(), PeriodType::Weekly)),
+    }
+}
 
-    // Strip ANSI escape codes
-    let stripped = strip_ansi_escapes::strip(raw_output);
-    let output = String::from_utf8_lossy(&stripped);
-    debug_log!("Stripped output ({} bytes):\n{}", output.len(), output);
+/// Parse the rendered /usage screen
+fn parse_usage_output(output: &str) -> Result<UsageData> {
+    debug_log!("Parsing screen ({} bytes):\n{}", output.len(), output);
 
     let mut data = UsageData::new();
 
@@ -66,115 +95,200 @@ fn parse_usage_output(raw_output: &str) -> Result<UsageData> {
     // Parse reset time patterns like "Resets 7pm" or "Resets Dec 8 at 4pm"
     let reset_re = Regex::new(r"Resets\s+([^\n]+)")?;
 
-    // Split by sections - look for "Current session", "Current week (all models)", etc.
+    // Weekly section headers: "Current week (all models)", "Current week (Sonnet only)", ...
+    let week_re = Regex::new(r"(?i)current week\s*(?:\(([^)]*)\))?")?;
+
+    // Split by sections - look for "Current session", "Current week (...)", etc.
     let lines: Vec<&str> = output.lines().collect();
 
-    let mut current_section = "";
+    // (id, label, period) of the section we're currently in
+    let mut current_section: Option<(String, String, PeriodType)> = None;
 
     for (i, line) in lines.iter().enumerate() {
         let line_lower = line.to_lowercase();
 
         // Detect sections
-        if line_lower.contains("current session") {
-            debug_log!("Line {}: Detected section 'session': {}", i, line);
-            current_section = "session";
-        } else if line_lower.contains("current week") && line_lower.contains("all models") {
-            debug_log!("Line {}: Detected section 'week_all': {}", i, line);
-            current_section = "week_all";
-        } else if line_lower.contains("current week") && line_lower.contains("sonnet") {
-            debug_log!("Line {}: Detected section 'week_sonnet': {}", i, line);
-            current_section = "week_sonnet";
+        if let Some(section) = parse_section_header(line, &week_re) {
+            debug_log!("Line {}: Detected section '{}': {}", i, section.0, line);
+            current_section = Some(section);
         } else if line_lower.contains("extra usage") {
             debug_log!("Line {}: Detected section 'extra': {}", i, line);
-            current_section = "extra";
+            current_section = None;
             if line_lower.contains("not enabled") {
                 debug_log!("  -> Extra usage: NOT enabled");
-                data.extra_usage_enabled = false;
+                data.extra_usage_enabled = Some(false);
             } else if line_lower.contains("enabled") {
                 debug_log!("  -> Extra usage: enabled");
-                data.extra_usage_enabled = true;
+                data.extra_usage_enabled = Some(true);
             }
         }
 
+        let Some((id, label, period)) = &current_section else {
+            continue;
+        };
+
         // Extract percentages
-        if let Some(caps) = percent_re.captures(line) {
-            if let Some(pct) = caps.get(1) {
-                let percent: f32 = pct.as_str().parse().unwrap_or(0.0);
-                debug_log!("Line {}: Found {}% in section '{}': {}", i, percent, current_section, line);
-                match current_section {
-                    "session" => data.current_session_percent = Some(percent),
-                    "week_all" => data.current_week_all_models_percent = Some(percent),
-                    "week_sonnet" => data.current_week_sonnet_percent = Some(percent),
-                    _ => { debug_log!("  -> Ignoring percentage (unknown section)"); },
-                }
+        if let Some(pct) = percent_re.captures(line).and_then(|caps| caps.get(1)) {
+            let percent: f32 = pct.as_str().parse().unwrap_or(0.0);
+            debug_log!("Line {}: Found {}% in section '{}': {}", i, percent, id, line);
+            match data.quotas.iter_mut().find(|q| &q.id == id) {
+                Some(quota) => quota.used_percent = percent,
+                None => data.quotas.push(Quota {
+                    id: id.clone(),
+                    label: label.clone(),
+                    used_percent: percent,
+                    reset: None,
+                    period: *period,
+                }),
             }
         }
 
         // Extract reset times
-        if let Some(caps) = reset_re.captures(line) {
-            if let Some(reset) = caps.get(1) {
-                let reset_str = reset.as_str().trim().to_string();
-                debug_log!("Line {}: Found reset '{}' in section '{}': {}", i, reset_str, current_section, line);
-                match current_section {
-                    "session" => data.current_session_reset = Some(reset_str),
-                    "week_all" => data.current_week_all_models_reset = Some(reset_str),
-                    "week_sonnet" => data.current_week_sonnet_reset = Some(reset_str),
-                    _ => { debug_log!("  -> Ignoring reset time (unknown section)"); },
-                }
+        if let Some(reset) = reset_re.captures(line).and_then(|caps| caps.get(1)) {
+            let reset_str = reset.as_str().trim().to_string();
+            debug_log!("Line {}: Found reset '{}' in section '{}': {}", i, reset_str, id, line);
+            match data.quotas.iter_mut().find(|q| &q.id == id) {
+                Some(quota) => quota.reset = Some(reset_str),
+                None => { debug_log!("  -> Ignoring reset time (no percentage in section yet)"); },
             }
         }
     }
 
+    if data.quotas.is_empty() {
+        let error = FetchError::from_screen(output, LOGIN_COMMAND)
+            .unwrap_or(FetchError::UnrecognizedOutput);
+        return Err(error.into());
+    }
+
     Ok(data)
 }
 
-fn run_claude_usage() -> Result<String> {
+/// PTY size; tall enough for the whole /usage panel to stay on screen
+const PTY_ROWS: u16 = 50;
+const PTY_COLS: u16 = 80;
+
+/// Give up waiting for the usage screen after this long
+const TIMEOUT_SECS: u64 = 30;
+
+/// Suggested when Claude Code isn't logged in
+const LOGIN_COMMAND: &str = "claude login";
+
+/// A Claude account, selected by the config directory the CLI runs with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClaudeProfile {
+    /// Shown in the menu and notifications, e.g. "Work"
+    pub name: String,
+    /// Binary to run; empty uses `CLAUDE_PATH` or `claude` from PATH
+    pub claude_path: String,
+    /// Passed as `CLAUDE_CONFIG_DIR`; empty uses the CLI's default
+    pub config_dir: String,
+    /// Extra environment variables for the CLI
+    pub env: BTreeMap<String, String>,
+}
+
+impl ClaudeProfile {
+    /// Provider id of the profile, e.g. "claude_work"
+    pub fn provider_id(&self) -> String {
+        format!("claude_{}", slugify(&self.name))
+    }
+
+    /// The config directory with a leading `~` expanded, None if not set. A
+    /// `CLAUDE_CONFIG_DIR` in `env` takes precedence, as it does for the CLI.
+    fn config_path(&self) -> Option<PathBuf> {
+        let dir = match self.env.get("CLAUDE_CONFIG_DIR") {
+            Some(dir) => dir.trim(),
+            None => self.config_dir.trim(),
+        };
+        if dir.is_empty() {
+            return None;
+        }
+        match dir.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().map(|home| home.join(rest)),
+            None => Some(PathBuf::from(dir)),
+        }
+    }
+
+    /// Transcript directory of the account the profile runs with
+    pub fn projects_dir(&self) -> Option<PathBuf> {
+        match self.config_path() {
+            Some(dir) => Some(dir.join("projects")),
+            None => claude_projects_dir(),
+        }
+    }
+
+    /// Validate the profile and return errors if invalid
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if slugify(&self.name).is_empty() {
+            errors.push("Every Claude profile needs a name".to_string());
+        }
+        if self.env.keys().any(|key| key.is_empty() || key.contains('=')) {
+            errors.push(format!("Invalid environment variable name in {}", self.name));
+        }
+        errors
+    }
+}
+
+/// Run `claude /usage` with the profile's binary and environment and return the
+/// rendered screen
+fn run_claude_usage(profile: &ClaudeProfile) -> Result<String> {
     debug_log!("Starting claude /usage fetch...");
     let pty_system = NativePtySystem::default();
 
     // Create a PTY with a reasonable size
     let pair = pty_system
         .openpty(PtySize {
-            rows: 24,
-            cols: 80,
+            rows: PTY_ROWS,
+            cols: PTY_COLS,
             pixel_width: 0,
             pixel_height: 0,
         })
         .context("Failed to open PTY")?;
 
-    // Build the command - look for claude in PATH or use CLAUDE_PATH env var
-    let claude_path = std::env::var("CLAUDE_PATH").unwrap_or_else(|_| "claude".to_string());
+    // Build the command - the profile's binary, else CLAUDE_PATH or claude in PATH
+    let claude_path = match profile.claude_path.trim() {
+        "" => std::env::var("CLAUDE_PATH").unwrap_or_else(|_| "claude".to_string()),
+        path => path.to_string(),
+    };
     debug_log!("Using claude path: {}", claude_path);
 
     let mut cmd = CommandBuilder::new(&claude_path);
     cmd.arg("--dangerously-skip-permissions");
     cmd.arg("/usage");
+    for (key, value) in &profile.env {
+        cmd.env(key, value);
+    }
+    if let Some(dir) = profile.config_path() {
+        cmd.env("CLAUDE_CONFIG_DIR", dir);
+    }
     debug_log!("Command: {} --dangerously-skip-permissions /usage", claude_path);
 
     // Spawn the process
     let mut child = pair
         .slave
         .spawn_command(cmd)
-        .context("Failed to spawn claude")?;
+        .map_err(|e| FetchError::from_spawn(&claude_path, e))?;
     debug_log!("Claude process spawned successfully");
 
     // Drop the slave to avoid blocking
     drop(pair.slave);
 
-    // Read from the master
+    // Read from the master and render into a virtual screen
     let mut reader = pair.master.try_clone_reader()?;
-    let mut output = String::new();
+    let mut terminal = VirtualTerminal::new(PTY_ROWS, PTY_COLS);
     let mut buffer = [0u8; 4096];
 
     let start = Instant::now();
-    let timeout = Duration::from_secs(30);
+    let timeout = Duration::from_secs(TIMEOUT_SECS);
     let mut saw_loading = false;
-    let mut current_screen = String::new();
+    let mut timed_out = false;
 
     loop {
         // Check timeout
         if start.elapsed() > timeout {
             debug_log!("Timeout reached after {:?}", start.elapsed());
+            timed_out = true;
             break;
         }
 
@@ -189,61 +303,50 @@ fn run_claude_usage() -> Result<String> {
                 debug_log!("Read {} bytes", n);
 
                 // Log raw chunk (escape control chars for readability)
-                let escaped_chunk: String = chunk.chars().map(|c| {
+                let escaped_chunk: String = chunk.chars().flat_map(|c| {
                     if c.is_control() && c != '\n' && c != '\r' {
                         format!("\\x{:02x}", c as u32).chars().collect::<Vec<_>>()
                     } else {
                         vec![c]
                     }
-                }).flatten().collect();
+                }).collect();
                 debug_log!("Raw chunk: {}", escaped_chunk);
 
-                output.push_str(&chunk);
-                current_screen.push_str(&chunk);
-
-                // Detect screen clear sequence [2J (clear screen)
-                if chunk.contains("\x1b[2J") || chunk.contains("[2J") {
-                    debug_log!("Screen clear detected");
-                    current_screen.clear();
-                    current_screen.push_str(&chunk);
-                }
-
-                // Strip ANSI from current screen only
-                let stripped = strip_ansi_escapes::strip(&current_screen);
-                let clean_screen = String::from_utf8_lossy(&stripped);
-
-                // Log stripped content periodically (every 500+ chars or significant change)
-                if clean_screen.len() > 100 {
-                    debug_log!("Clean screen length: {} chars", clean_screen.len());
-                    // Show first 200 chars of clean screen (safely handle UTF-8 boundaries)
-                    let preview: String = clean_screen.chars().take(200).collect();
-                    debug_log!("Clean screen start: {}", preview.replace('\n', "\\n"));
-                }
+                terminal.feed(&buffer[..n]);
+                let screen = terminal.contents();
 
-                // Check current screen state
-                let has_loading = clean_screen.contains("Loading usage data");
-                let has_percent = clean_screen.contains("% used");
-                let has_current_session = clean_screen.contains("Current session");
-                let has_extra_usage = clean_screen.contains("Extra usage");
+                // Check rendered screen state. Line updates ([2K[1A) and screen
+                // clears are applied by the terminal, so stale lines are gone.
+                let has_loading = screen.contains("Loading usage data");
+                let has_percent = screen.contains("% used");
+                let has_current_session = screen.contains("Current session");
+                let has_extra_usage = screen.contains("Extra usage");
 
                 debug_log!(
                     "State: loading={}, percent={}, session={}, extra={}",
                     has_loading, has_percent, has_current_session, has_extra_usage
                 );
 
+                // A login prompt waits for input, so stop waiting
+                if !has_percent {
+                    if let Some(error @ FetchError::NotLoggedIn { .. }) =
+                        FetchError::from_screen(&screen, LOGIN_COMMAND)
+                    {
+                        debug_log!("Error screen ({}):\n{}", error, screen);
+                        let _ = child.kill();
+                        return Err(error.into());
+                    }
+                }
+
                 if has_loading && !saw_loading {
                     debug_log!("Detected loading screen");
                     saw_loading = true;
                 }
 
-                // Success: Current screen has all required usage data
-                // Note: We don't check !has_loading because the terminal uses line-by-line
-                // updates ([2K[1A sequences) rather than full screen clears, so old content
-                // can linger in our buffer. The presence of all data fields is sufficient.
-                if has_percent && has_current_session && has_extra_usage {
+                // Success: screen shows all required usage data and nothing is loading
+                if has_percent && has_current_session && has_extra_usage && !has_loading {
                     debug_log!("Success: Found complete usage data");
-                    debug_log!("Final clean screen:\n{}", clean_screen);
-                    output = current_screen;
+                    debug_log!("Final screen:\n{}", screen);
                     break;
                 }
             }
@@ -270,8 +373,7 @@ fn run_claude_usage() -> Result<String> {
                     }
                     Ok(n) => {
                         drained_bytes += n;
-                        let chunk = String::from_utf8_lossy(&buffer[..n]);
-                        output.push_str(&chunk);
+                        terminal.feed(&buffer[..n]);
                     }
                     Err(e) => {
                         debug_log!("Drain ended with error: {}", e);
@@ -287,32 +389,106 @@ fn run_claude_usage() -> Result<String> {
     debug_log!("Loop ended, killing process if still running");
     let _ = child.kill();
 
-    debug_log!("Total output length: {} bytes", output.len());
+    let output = terminal.contents();
+    debug_log!("Rendered screen length: {} bytes", output.len());
+    if timed_out && !output.contains("% used") {
+        return Err(FetchError::Timeout { secs: TIMEOUT_SECS }.into());
+    }
     Ok(output)
 }
 
 /// Fetch usage data from Claude Code
-pub fn fetch_usage() -> Result<UsageData> {
-    let raw_output = run_claude_usage()?;
-    let data = parse_usage_output(&raw_output)?;
+pub fn fetch_usage(profile: &ClaudeProfile) -> Result<UsageData> {
+    let screen = run_claude_usage(profile)?;
+    let data = parse_usage_output(&screen)?;
 
     debug_log!(
-        "Parsed data: session={:?}%, week_all={:?}%, week_sonnet={:?}%, extra={}",
-        data.current_session_percent,
-        data.current_week_all_models_percent,
-        data.current_week_sonnet_percent,
+        "Parsed data: quotas={:?}, extra={:?}",
+        data.quotas
+            .iter()
+            .map(|q| (&q.id, q.used_percent))
+            .collect::<Vec<_>>(),
         data.extra_usage_enabled
     );
 
-    // Warn if no data was actually parsed
-    if data.current_session_percent.is_none()
-        && data.current_week_all_models_percent.is_none()
-        && data.current_week_sonnet_percent.is_none()
-    {
-        debug_log!("WARNING: No usage percentages were parsed from the output!");
+    Ok(data)
+}
+
+/// Claude Code quotas known to appear in `/usage`; other "Current week (...)"
+/// sections are picked up dynamically
+const CLAUDE_QUOTAS: [QuotaSpec; 3] = [
+    QuotaSpec {
+        id: "session",
+        label: "Session",
+        period: PeriodType::Session,
+    },
+    QuotaSpec {
+        id: "week_all_models",
+        label: "Week (all models)",
+        period: PeriodType::Weekly,
+    },
+    QuotaSpec {
+        id: "week_sonnet_only",
+        label: "Week (Sonnet only)",
+        period: PeriodType::Weekly,
+    },
+];
+
+/// Claude Code usage provider (drives `claude /usage`)
+pub struct ClaudeProvider {
+    id: String,
+    display_name: String,
+    profile: ClaudeProfile,
+}
+
+impl ClaudeProvider {
+    /// Provider for a named profile, or for the default account with `None`
+    pub fn new(profile: Option<&ClaudeProfile>) -> Self {
+        match profile {
+            Some(profile) => Self {
+                id: profile.provider_id(),
+                display_name: format!("Claude ({})", profile.name.trim()),
+                profile: profile.clone(),
+            },
+            None => Self {
+                id: "claude".to_string(),
+                display_name: "Claude".to_string(),
+                profile: ClaudeProfile::default(),
+            },
+        }
+    }
+}
+
+impl UsageProvider for ClaudeProvider {
+    fn id(&self) -> &str {
+        &self.id
     }
 
-    Ok(data)
+    fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    fn quotas(&self) -> &[QuotaSpec] {
+        &CLAUDE_QUOTAS
+    }
+
+    fn fetch(&self) -> Result<UsageData> {
+        fetch_usage(&self.profile)
+    }
+
+    fn transcripts(&self) -> Option<TranscriptScanner> {
+        let dir = self.profile.projects_dir()?;
+        Some(TranscriptScanner::new(dir, Box::new(ClaudeFormat)))
+    }
+
+    fn extra_lines(&self, usage: &UsageData) -> Vec<String> {
+        let extra_text = if usage.extra_usage_enabled == Some(true) {
+            "enabled"
+        } else {
+            "not enabled"
+        };
+        vec![format!("Extra usage: {}", extra_text)]
+    }
 }
 
 #[cfg(test)]
@@ -339,21 +515,50 @@ Extra usage not enabled • /extra-usage to enable
 ";
         let data = parse_usage_output(sample_output).unwrap();
 
-        assert_eq!(data.current_session_percent, Some(17.0));
-        assert_eq!(
-            data.current_session_reset.as_deref(),
-            Some("1:59am (America/Sao_Paulo)")
-        );
-        assert_eq!(data.current_week_all_models_percent, Some(57.0));
+        assert_eq!(data.quotas.len(), 3);
+
+        let session = &data.quotas[0];
+        assert_eq!(session.id, "session");
+        assert_eq!(session.used_percent, 17.0);
+        assert_eq!(session.reset.as_deref(), Some("1:59am (America/Sao_Paulo)"));
+
+        let week_all = &data.quotas[1];
+        assert_eq!(week_all.id, "week_all_models");
+        assert_eq!(week_all.label, "Week (all models)");
+        assert_eq!(week_all.used_percent, 57.0);
         assert_eq!(
-            data.current_week_all_models_reset.as_deref(),
+            week_all.reset.as_deref(),
             Some("Dec 22 at 3:59pm (America/Sao_Paulo)")
         );
-        assert_eq!(data.current_week_sonnet_percent, Some(8.0));
+
+        let week_sonnet = &data.quotas[2];
+        assert_eq!(week_sonnet.id, "week_sonnet_only");
+        assert_eq!(week_sonnet.used_percent, 8.0);
         assert_eq!(
-            data.current_week_sonnet_reset.as_deref(),
+            week_sonnet.reset.as_deref(),
             Some("Dec 22 at 3:59pm (America/Sao_Paulo)")
         );
-        assert_eq!(data.extra_usage_enabled, false);
+        assert_eq!(data.extra_usage_enabled, Some(false));
+    }
+
+    #[test]
+    fn test_parse_usage_output_captures_new_week_sections() {
+        let sample_output = "\
+Current session
+████████▌                                          17% used
+Resets 1:59am (America/Sao_Paulo)
+
+Current week (Opus only)
+██                                                 4% used
+Resets Dec 22 at 3:59pm (America/Sao_Paulo)
+";
+        let data = parse_usage_output(sample_output).unwrap();
+
+        let opus = &data.quotas[1];
+        assert_eq!(opus.id, "week_opus_only");
+        assert_eq!(opus.label, "Week (Opus only)");
+        assert_eq!(opus.used_percent, 4.0);
+        assert_eq!(opus.period, PeriodType::Weekly);
+        assert_eq!(data.extra_usage_enabled, None);
     }
 }
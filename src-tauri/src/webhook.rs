@@ -0,0 +1,136 @@
+use anyhow::{bail, Result};
+use chrono::{Duration as ChronoDuration, Local};
+use serde_json::Value;
+use std::thread;
+use std::time::Duration;
+
+use crate::alert::{alert_json, AlertChannel};
+use crate::notification::{NotificationInfo, NotificationSeverity, QuotaType};
+
+/// Delivery attempts per alert before giving up
+const ATTEMPTS: u32 = 4;
+/// Delay before the first retry; doubled after every failed attempt
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+/// Per-request timeout
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// POSTs each alert as JSON to a URL, retrying with exponential backoff
+pub struct WebhookChannel {
+    url: String,
+}
+
+impl WebhookChannel {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+}
+
+impl AlertChannel for WebhookChannel {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    /// Delivery (including retries) runs on its own thread so a slow or
+    /// unreachable target doesn't hold up the refresh
+    fn send(&self, info: &NotificationInfo) -> Result<()> {
+        let url = self.url.clone();
+        let payload = alert_json(info);
+        thread::spawn(move || {
+            if let Err(e) = post_with_retry(&url, &payload, ATTEMPTS, INITIAL_BACKOFF) {
+                eprintln!("[NotifAI] Webhook {} failed: {:#}", url, e);
+            }
+        });
+        Ok(())
+    }
+}
+
+/// POST `payload` to `url`, retrying failed attempts (network errors or non-2xx)
+pub fn post_with_retry(url: &str, payload: &Value, attempts: u32, backoff: Duration) -> Result<()> {
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    let mut delay = backoff;
+    let mut last_error = String::new();
+
+    for attempt in 1..=attempts {
+        match agent.post(url).send_json(payload) {
+            Ok(_) => return Ok(()),
+            Err(ureq::Error::Status(code, _)) => last_error = format!("HTTP {}", code),
+            Err(e) => last_error = e.to_string(),
+        }
+        if attempt < attempts {
+            eprintln!(
+                "[NotifAI] Webhook attempt {}/{} failed ({}), retrying in {}s",
+                attempt,
+                attempts,
+                last_error,
+                delay.as_secs_f32()
+            );
+            thread::sleep(delay);
+            delay *= 2;
+        }
+    }
+
+    bail!("{} after {} attempts", last_error, attempts)
+}
+
+/// Send a sample alert to a webhook, for the settings window's test button
+pub fn send_test(url: &str) -> Result<()> {
+    let info = NotificationInfo {
+        quota_type: QuotaType::new("notifai", "test"),
+        label: "NotifAI Test".to_string(),
+        severity: NotificationSeverity::Approaching,
+        projected_percent: 100.0,
+        reset_time: Local::now() + ChronoDuration::hours(5),
+        exhaustion_at: None,
+        digest: Vec::new(),
+        detail: None,
+    };
+    post_with_retry(url, &alert_json(&info), 1, INITIAL_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiny_http::{Response, Server};
+
+    #[test]
+    fn test_retries_until_success() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.server_addr().to_ip().unwrap());
+
+        let handle = thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for status in [500, 200] {
+                let mut request = server.recv().unwrap();
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                bodies.push(body);
+                request.respond(Response::empty(status)).unwrap();
+            }
+            bodies
+        });
+
+        let payload = serde_json::json!({ "severity": "over_budget" });
+        post_with_retry(&url, &payload, 3, Duration::from_millis(10)).unwrap();
+
+        let bodies = handle.join().unwrap();
+        assert_eq!(bodies.len(), 2);
+        let received: Value = serde_json::from_str(&bodies[1]).unwrap();
+        assert_eq!(received["severity"], "over_budget");
+    }
+
+    #[test]
+    fn test_gives_up_after_attempts() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.server_addr().to_ip().unwrap());
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let _ = request.respond(Response::empty(503));
+            }
+        });
+
+        let err = post_with_retry(&url, &Value::Null, 2, Duration::from_millis(10)).unwrap_err();
+        assert_eq!(err.to_string(), "HTTP 503 after 2 attempts");
+    }
+}
@@ -0,0 +1,63 @@
+const { invoke } = window.__TAURI__.core;
+const { getCurrentWindow } = window.__TAURI__.window;
+
+const quotasDiv = document.getElementById('quotas');
+const emptyText = document.getElementById('empty');
+
+// One bar segment per project, then "other"
+function renderQuota(attribution) {
+  const section = document.createElement('section');
+  section.className = 'quota';
+
+  const title = document.createElement('h2');
+  title.textContent = `${attribution.provider_name} ${attribution.label}: ${Math.round(attribution.used_percent)}%`;
+  section.appendChild(title);
+
+  const parts = attribution.projects.map(p => [p.project, p.percent]);
+  if (attribution.other_percent >= 0.5) {
+    parts.push(['other', attribution.other_percent]);
+  }
+
+  const bar = document.createElement('div');
+  bar.className = 'bar';
+  const list = document.createElement('ul');
+  parts.forEach(([name, percent], i) => {
+    const segment = document.createElement('span');
+    segment.className = name === 'other' ? 'segment other' : `segment color-${i % 5}`;
+    segment.style.width = `${Math.min(percent, 100)}%`;
+    segment.title = `${name} ${Math.round(percent)}%`;
+    bar.appendChild(segment);
+
+    const item = document.createElement('li');
+    item.textContent = `${name} ${Math.round(percent)}%`;
+    list.appendChild(item);
+  });
+  section.appendChild(bar);
+  section.appendChild(list);
+  return section;
+}
+
+async function loadAttribution() {
+  let attributions = [];
+  try {
+    attributions = await invoke('get_attribution');
+  } catch (error) {
+    console.error('Failed to load attribution:', error);
+  }
+
+  quotasDiv.innerHTML = '';
+  attributions.forEach(a => quotasDiv.appendChild(renderQuota(a)));
+  emptyText.hidden = attributions.length > 0;
+}
+
+document.getElementById('reload-btn').addEventListener('click', loadAttribution);
+
+// Close on Escape key
+document.addEventListener('keydown', async (e) => {
+  if (e.key === 'Escape') {
+    e.preventDefault();
+    await getCurrentWindow().close();
+  }
+});
+
+loadAttribution();
@@ -8,23 +8,86 @@ const resetBtn = document.getElementById('reset-btn');
 const cancelBtn = document.getElementById('cancel-btn');
 const saveBtn = document.getElementById('save-btn');
 const notificationsEnabled = document.getElementById('notifications-enabled');
+const scheduleEnabled = document.getElementById('schedule-enabled');
+const apiEnabled = document.getElementById('api-enabled');
+const geminiEnabled = document.getElementById('gemini-enabled');
+const testWebhookBtn = document.getElementById('test-webhook-btn');
+const webhookStatus = document.getElementById('webhook-status');
+const scheduleDays = document.querySelectorAll('#schedule-days input[type="checkbox"]');
+const rulesList = document.getElementById('rules-list');
+const addRuleBtn = document.getElementById('add-rule-btn');
+const profilesList = document.getElementById('profiles-list');
+const addProfileBtn = document.getElementById('add-profile-btn');
+const quietEnabled = document.getElementById('quiet-enabled');
+const quietDays = document.querySelectorAll('#quiet-days .quiet-day');
 
 // Track if form is dirty
 let originalSettings = null;
 
+// Quotas rules can target, from the registered providers
+let quotaOptions = [];
+
 // Default values
 const DEFAULTS = {
   refresh_interval_minutes: 15,
   threshold_under_budget: 85,
   threshold_on_track: 115,
   notifications_enabled: true,
-  notify_approaching_percent: 100,
-  notify_over_budget_percent: 115,
-  codex_path: 'codex'
+  notification_rules: [
+    { provider: '', quota: '', severity: 'approaching', min_projected_percent: 100 },
+    { provider: '', quota: '', severity: 'over_budget', min_projected_percent: 115 },
+    { provider: '', quota: '', severity: 'exhaustion', exhaustion: true }
+  ],
+  notify_on_reset: true,
+  failure_alert_after: 3,
+  quiet_hours: {
+    enabled: false,
+    ranges: [0, 1, 2, 3, 4, 5, 6].map(day => ({ day, start_hour: 22, end_hour: 7 })),
+    timezone: '',
+    digest: true
+  },
+  claude_profiles: [],
+  codex_path: 'codex',
+  gemini_enabled: false,
+  gemini_path: 'gemini',
+  projection_method: 'burn_rate',
+  work_schedule: {
+    enabled: false,
+    days: [0, 1, 2, 3, 4],
+    start_hour: 9,
+    end_hour: 18,
+    timezone: ''
+  },
+  api_enabled: false,
+  api_port: 7423,
+  api_token: '',
+  metrics_enabled: false,
+  webhook_urls: [],
+  pricing: [
+    { model: 'claude-opus-4-5', input: 5, output: 25, cache_write: 6.25, cache_read: 0.5 },
+    { model: 'claude-opus-4', input: 15, output: 75, cache_write: 18.75, cache_read: 1.5 },
+    { model: 'claude-sonnet-4', input: 3, output: 15, cache_write: 3.75, cache_read: 0.3 },
+    { model: 'claude-3-7-sonnet', input: 3, output: 15, cache_write: 3.75, cache_read: 0.3 },
+    { model: 'claude-haiku-4-5', input: 1, output: 5, cache_write: 1.25, cache_read: 0.1 },
+    { model: 'claude-3-5-haiku', input: 0.8, output: 4, cache_write: 1, cache_read: 0.08 },
+    { model: 'gpt-5', input: 1.25, output: 10, cache_write: 0, cache_read: 0.125 },
+    { model: 'gpt-5-mini', input: 0.25, output: 2, cache_write: 0, cache_read: 0.025 }
+  ],
+  hooks: {
+    on_notification: '',
+    on_reset: '',
+    on_status_change: ''
+  }
 };
 
 // Load settings on page load
 async function loadSettings() {
+  try {
+    quotaOptions = await invoke('get_quota_options');
+  } catch (error) {
+    console.error('Failed to load quotas:', error);
+  }
+
   try {
     const settings = await invoke('get_settings');
     populateForm(settings);
@@ -41,11 +104,56 @@ function populateForm(settings) {
   document.getElementById('under-budget').value = settings.threshold_under_budget;
   document.getElementById('on-track').value = settings.threshold_on_track;
   document.getElementById('notifications-enabled').checked = settings.notifications_enabled;
-  document.getElementById('notify-approaching').value = settings.notify_approaching_percent;
-  document.getElementById('notify-over').value = settings.notify_over_budget_percent;
+  rulesList.innerHTML = '';
+  (settings.notification_rules || DEFAULTS.notification_rules).forEach(addRuleRow);
+  document.getElementById('notify-on-reset').checked = settings.notify_on_reset ?? DEFAULTS.notify_on_reset;
+  document.getElementById('failure-alert-after').value = settings.failure_alert_after ?? DEFAULTS.failure_alert_after;
+  profilesList.innerHTML = '';
+  (settings.claude_profiles || DEFAULTS.claude_profiles).forEach(addProfileRow);
   document.getElementById('codex-path').value = settings.codex_path || 'codex';
+  geminiEnabled.checked = settings.gemini_enabled ?? DEFAULTS.gemini_enabled;
+  document.getElementById('gemini-path').value = settings.gemini_path || DEFAULTS.gemini_path;
+  document.getElementById('projection-method').value = settings.projection_method || 'burn_rate';
+
+  const schedule = settings.work_schedule || DEFAULTS.work_schedule;
+  scheduleEnabled.checked = schedule.enabled;
+  scheduleDays.forEach(el => {
+    el.checked = schedule.days.includes(parseInt(el.value));
+  });
+  document.getElementById('schedule-start').value = schedule.start_hour;
+  document.getElementById('schedule-end').value = schedule.end_hour;
+  document.getElementById('schedule-timezone').value = schedule.timezone || '';
+
+  const quiet = settings.quiet_hours || DEFAULTS.quiet_hours;
+  quietEnabled.checked = quiet.enabled;
+  quietDays.forEach(row => {
+    const range = quiet.ranges.find(r => r.day === parseInt(row.dataset.day));
+    row.querySelector('input[type="checkbox"]').checked = !!range;
+    row.querySelector('.quiet-start').value = range ? range.start_hour : 22;
+    row.querySelector('.quiet-end').value = range ? range.end_hour : 7;
+  });
+  document.getElementById('quiet-timezone').value = quiet.timezone || '';
+  document.getElementById('quiet-digest').checked = quiet.digest;
+
+  apiEnabled.checked = settings.api_enabled;
+  document.getElementById('api-port').value = settings.api_port || DEFAULTS.api_port;
+  document.getElementById('api-token').value = settings.api_token || '';
+  document.getElementById('metrics-enabled').checked = settings.metrics_enabled;
+  document.getElementById('webhook-urls').value = (settings.webhook_urls || []).join('\n');
+  document.getElementById('pricing').value = (settings.pricing || DEFAULTS.pricing)
+    .map(p => [p.model, p.input, p.output, p.cache_write, p.cache_read].join(' '))
+    .join('\n');
+
+  const hooks = settings.hooks || DEFAULTS.hooks;
+  document.getElementById('hook-notification').value = hooks.on_notification;
+  document.getElementById('hook-reset').value = hooks.on_reset;
+  document.getElementById('hook-status-change').value = hooks.on_status_change;
 
   updateNotificationFieldsState();
+  updateScheduleFieldsState();
+  updateQuietFieldsState();
+  updateApiFieldsState();
+  updateGeminiFieldsState();
 }
 
 function getFormValues() {
@@ -54,12 +162,182 @@ function getFormValues() {
     threshold_under_budget: parseFloat(document.getElementById('under-budget').value),
     threshold_on_track: parseFloat(document.getElementById('on-track').value),
     notifications_enabled: document.getElementById('notifications-enabled').checked,
-    notify_approaching_percent: parseFloat(document.getElementById('notify-approaching').value),
-    notify_over_budget_percent: parseFloat(document.getElementById('notify-over').value),
-    codex_path: document.getElementById('codex-path').value.trim()
+    notification_rules: getRules(),
+    notify_on_reset: document.getElementById('notify-on-reset').checked,
+    failure_alert_after: parseInt(document.getElementById('failure-alert-after').value),
+    claude_profiles: getProfiles(),
+    codex_path: document.getElementById('codex-path').value.trim(),
+    gemini_enabled: geminiEnabled.checked,
+    gemini_path: document.getElementById('gemini-path').value.trim(),
+    projection_method: document.getElementById('projection-method').value,
+    work_schedule: {
+      enabled: scheduleEnabled.checked,
+      days: Array.from(scheduleDays).filter(el => el.checked).map(el => parseInt(el.value)),
+      start_hour: parseInt(document.getElementById('schedule-start').value),
+      end_hour: parseInt(document.getElementById('schedule-end').value),
+      timezone: document.getElementById('schedule-timezone').value.trim()
+    },
+    quiet_hours: {
+      enabled: quietEnabled.checked,
+      ranges: Array.from(quietDays)
+        .filter(row => row.querySelector('input[type="checkbox"]').checked)
+        .map(row => ({
+          day: parseInt(row.dataset.day),
+          start_hour: parseInt(row.querySelector('.quiet-start').value),
+          end_hour: parseInt(row.querySelector('.quiet-end').value)
+        })),
+      timezone: document.getElementById('quiet-timezone').value.trim(),
+      digest: document.getElementById('quiet-digest').checked
+    },
+    api_enabled: apiEnabled.checked,
+    api_port: parseInt(document.getElementById('api-port').value),
+    api_token: document.getElementById('api-token').value.trim(),
+    metrics_enabled: document.getElementById('metrics-enabled').checked,
+    webhook_urls: document.getElementById('webhook-urls').value
+      .split('\n')
+      .map(url => url.trim())
+      .filter(url => url.length > 0),
+    pricing: getPricing(),
+    hooks: {
+      on_notification: document.getElementById('hook-notification').value.trim(),
+      on_reset: document.getElementById('hook-reset').value.trim(),
+      on_status_change: document.getElementById('hook-status-change').value.trim()
+    }
   };
 }
 
+// "model input output cache_write cache_read" per line
+function getPricing() {
+  return document.getElementById('pricing').value
+    .split('\n')
+    .map(line => line.trim().split(/\s+/))
+    .filter(parts => parts[0].length > 0)
+    .map(([model, ...prices]) => {
+      const [input, output, cache_write, cache_read] = [0, 1, 2, 3].map(i => parseFloat(prices[i]));
+      return { model, input, output, cache_write, cache_read };
+    });
+}
+
+// Target options: any quota, any quota of a provider, or a single quota
+function ruleTargetOptions() {
+  const options = [['', 'Any quota']];
+  const providers = [...new Set(quotaOptions.map(q => q.provider))];
+  providers.forEach(provider => {
+    const name = quotaOptions.find(q => q.provider === provider).provider_name;
+    options.push([`${provider}/`, `Any ${name} quota`]);
+    quotaOptions
+      .filter(q => q.provider === provider)
+      .forEach(q => options.push([`${q.provider}/${q.quota}`, q.label]));
+  });
+  return options;
+}
+
+function addRuleRow(rule) {
+  const row = document.createElement('div');
+  row.className = 'rule-row';
+  const target = rule.provider ? `${rule.provider}/${rule.quota || ''}` : '';
+  const number = (field, value, placeholder) =>
+    `<input type="number" class="${field}" min="0" step="1" placeholder="${placeholder}" value="${value ?? ''}">`;
+  row.innerHTML = `
+    <div class="rule-line">
+      <select class="rule-target">
+        ${ruleTargetOptions().map(([value, label]) => `<option value="${value}">${label}</option>`).join('')}
+      </select>
+      <select class="rule-severity">
+        <option value="approaching">Approaching</option>
+        <option value="over_budget">Over budget</option>
+        <option value="exhaustion">Will run out</option>
+      </select>
+      <button type="button" class="secondary rule-remove" title="Remove rule">×</button>
+    </div>
+    <div class="rule-line">
+      ${number('rule-current', rule.min_current_percent, 'Current ≥ %')}
+      ${number('rule-projected', rule.min_projected_percent, 'Projected ≥ %')}
+      ${number('rule-min-remaining', rule.min_remaining_minutes, 'Left > min')}
+      ${number('rule-max-remaining', rule.max_remaining_minutes, 'Left < min')}
+      <label><input type="checkbox" class="rule-exhaustion"${rule.exhaustion ? ' checked' : ''}>Runs out</label>
+    </div>`;
+  const targetSelect = row.querySelector('.rule-target');
+  if (![...targetSelect.options].some(o => o.value === target)) {
+    targetSelect.add(new Option(target, target));
+  }
+  targetSelect.value = target;
+  row.querySelector('.rule-severity').value = rule.severity || 'approaching';
+  row.querySelector('.rule-remove').addEventListener('click', () => {
+    row.remove();
+    showErrors(validateForm());
+  });
+  rulesList.appendChild(row);
+}
+
+function getRules() {
+  const optional = (row, field, parse) => {
+    const value = row.querySelector(field).value;
+    return value === '' ? null : parse(value);
+  };
+  return Array.from(rulesList.querySelectorAll('.rule-row')).map(row => {
+    const [provider, quota] = row.querySelector('.rule-target').value.split('/');
+    return {
+      provider: provider || '',
+      quota: quota || '',
+      severity: row.querySelector('.rule-severity').value,
+      min_current_percent: optional(row, '.rule-current', parseFloat),
+      min_projected_percent: optional(row, '.rule-projected', parseFloat),
+      min_remaining_minutes: optional(row, '.rule-min-remaining', v => parseInt(v)),
+      max_remaining_minutes: optional(row, '.rule-max-remaining', v => parseInt(v)),
+      exhaustion: row.querySelector('.rule-exhaustion').checked
+    };
+  });
+}
+
+function addProfileRow(profile) {
+  const row = document.createElement('div');
+  row.className = 'profile-row';
+  row.innerHTML = `
+    <div class="rule-line">
+      <input type="text" class="profile-name" placeholder="Name, e.g. Work">
+      <button type="button" class="secondary profile-remove" title="Remove profile">×</button>
+    </div>
+    <div class="rule-line">
+      <input type="text" class="profile-path" placeholder="Binary (default: claude)">
+      <input type="text" class="profile-config-dir" placeholder="Config dir, e.g. ~/.claude-work">
+    </div>
+    <div class="rule-line">
+      <textarea class="profile-env" rows="2" placeholder="Extra environment, one KEY=value per line"></textarea>
+    </div>`;
+  row.querySelector('.profile-name').value = profile.name || '';
+  row.querySelector('.profile-path').value = profile.claude_path || '';
+  row.querySelector('.profile-config-dir').value = profile.config_dir || '';
+  row.querySelector('.profile-env').value = Object.entries(profile.env || {})
+    .map(([key, value]) => `${key}=${value}`)
+    .join('\n');
+  row.querySelector('.profile-remove').addEventListener('click', () => {
+    row.remove();
+    showErrors(validateForm());
+  });
+  profilesList.appendChild(row);
+}
+
+function getProfiles() {
+  return Array.from(profilesList.querySelectorAll('.profile-row')).map(row => {
+    const env = {};
+    row.querySelector('.profile-env').value
+      .split('\n')
+      .map(line => line.trim())
+      .filter(line => line.length > 0)
+      .forEach(line => {
+        const eq = line.indexOf('=');
+        env[eq < 0 ? line : line.slice(0, eq)] = eq < 0 ? '' : line.slice(eq + 1);
+      });
+    return {
+      name: row.querySelector('.profile-name').value.trim(),
+      claude_path: row.querySelector('.profile-path').value.trim(),
+      config_dir: row.querySelector('.profile-config-dir').value.trim(),
+      env
+    };
+  });
+}
+
 function updateNotificationFieldsState() {
   const enabled = notificationsEnabled.checked;
   document.querySelectorAll('.notification-field').forEach(el => {
@@ -67,6 +345,34 @@ function updateNotificationFieldsState() {
   });
 }
 
+function updateScheduleFieldsState() {
+  const enabled = scheduleEnabled.checked;
+  document.querySelectorAll('.schedule-field').forEach(el => {
+    el.classList.toggle('disabled', !enabled);
+  });
+}
+
+function updateQuietFieldsState() {
+  const enabled = quietEnabled.checked;
+  document.querySelectorAll('.quiet-field').forEach(el => {
+    el.classList.toggle('disabled', !enabled);
+  });
+}
+
+function updateApiFieldsState() {
+  const enabled = apiEnabled.checked;
+  document.querySelectorAll('.api-field').forEach(el => {
+    el.classList.toggle('disabled', !enabled);
+  });
+}
+
+function updateGeminiFieldsState() {
+  const enabled = geminiEnabled.checked;
+  document.querySelectorAll('.gemini-field').forEach(el => {
+    el.classList.toggle('disabled', !enabled);
+  });
+}
+
 function validateForm() {
   const values = getFormValues();
   const errors = [];
@@ -83,22 +389,78 @@ function validateForm() {
     errors.push('Under budget threshold must be less than over budget threshold');
   }
 
-  if (values.notify_approaching_percent < 1 || values.notify_approaching_percent > 200) {
-    errors.push('Approaching notification threshold must be between 1 and 200%');
+  values.notification_rules.forEach((rule, i) => {
+    const conditions = [
+      rule.min_current_percent,
+      rule.min_projected_percent,
+      rule.min_remaining_minutes,
+      rule.max_remaining_minutes
+    ];
+    if (conditions.every(c => c === null) && !rule.exhaustion) {
+      errors.push(`Rule ${i + 1}: add at least one condition`);
+    }
+  });
+
+  const profileNames = values.claude_profiles.map(p => p.name.toLowerCase());
+  values.claude_profiles.forEach((profile, i) => {
+    if (profile.name.length === 0) {
+      errors.push(`Profile ${i + 1}: every Claude profile needs a name`);
+    }
+    if (Object.keys(profile.env).some(key => key.length === 0)) {
+      errors.push(`Profile ${i + 1}: use KEY=value for environment variables`);
+    }
+  });
+  if (new Set(profileNames).size < profileNames.length) {
+    errors.push('Claude profile names must be unique');
+  }
+
+  if (values.codex_path.length === 0) {
+    errors.push('Codex path cannot be empty');
+  }
+
+  if (values.gemini_enabled && values.gemini_path.length === 0) {
+    errors.push('Gemini path cannot be empty');
   }
 
-  if (values.notify_over_budget_percent < 1 || values.notify_over_budget_percent > 200) {
-    errors.push('Over budget notification threshold must be between 1 and 200%');
+  const schedule = values.work_schedule;
+  if (schedule.enabled && schedule.days.length === 0) {
+    errors.push('Select at least one working day');
   }
 
-  if (values.notify_over_budget_percent < values.notify_approaching_percent) {
-    errors.push('Over budget notification must be >= approaching notification');
+  if (schedule.start_hour < 0 || schedule.start_hour > 23 || schedule.end_hour < 1 || schedule.end_hour > 24) {
+    errors.push('Working hours must be within 0-24');
   }
 
-  if (values.codex_path.length === 0) {
-    errors.push('Codex path cannot be empty');
+  if (schedule.start_hour >= schedule.end_hour) {
+    errors.push('Working hours must start before they end');
   }
 
+  const quietRanges = values.quiet_hours.ranges;
+  if (quietRanges.some(r => r.start_hour < 0 || r.start_hour > 23 || r.end_hour < 0 || r.end_hour > 24)) {
+    errors.push('Quiet hours must be within 0-24');
+  }
+
+  if (quietRanges.some(r => r.start_hour === r.end_hour)) {
+    errors.push('Quiet hours must not start and end at the same hour');
+  }
+
+  if (!(values.api_port >= 1024 && values.api_port <= 65535)) {
+    errors.push('API port must be between 1024 and 65535');
+  }
+
+  values.webhook_urls.forEach(url => {
+    if (!url.startsWith('http://') && !url.startsWith('https://')) {
+      errors.push(`Webhook URL must start with http:// or https://: ${url}`);
+    }
+  });
+
+  values.pricing.forEach(price => {
+    const prices = [price.input, price.output, price.cache_write, price.cache_read];
+    if (prices.some(p => !(p >= 0))) {
+      errors.push(`Prices for ${price.model} need four amounts of 0 or more`);
+    }
+  });
+
   return errors;
 }
 
@@ -118,6 +480,10 @@ form.addEventListener('change', () => {
 });
 
 notificationsEnabled.addEventListener('change', updateNotificationFieldsState);
+scheduleEnabled.addEventListener('change', updateScheduleFieldsState);
+quietEnabled.addEventListener('change', updateQuietFieldsState);
+apiEnabled.addEventListener('change', updateApiFieldsState);
+geminiEnabled.addEventListener('change', updateGeminiFieldsState);
 
 form.addEventListener('submit', async (e) => {
   e.preventDefault();
@@ -142,6 +508,35 @@ form.addEventListener('submit', async (e) => {
   }
 });
 
+testWebhookBtn.addEventListener('click', async () => {
+  const urls = getFormValues().webhook_urls;
+  if (urls.length === 0) {
+    webhookStatus.textContent = 'Add a webhook URL first';
+    return;
+  }
+
+  testWebhookBtn.disabled = true;
+  const results = [];
+  for (const url of urls) {
+    try {
+      await invoke('test_webhook', { url });
+      results.push(`${url}: sent`);
+    } catch (error) {
+      results.push(`${url}: ${error}`);
+    }
+  }
+  webhookStatus.textContent = results.join(' · ');
+  testWebhookBtn.disabled = false;
+});
+
+addRuleBtn.addEventListener('click', () => {
+  addRuleRow({ provider: '', quota: '', severity: 'approaching' });
+});
+
+addProfileBtn.addEventListener('click', () => {
+  addProfileRow({});
+});
+
 resetBtn.addEventListener('click', () => {
   populateForm(DEFAULTS);
   showErrors([]);